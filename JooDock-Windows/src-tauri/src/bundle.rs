@@ -0,0 +1,461 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::commands::files;
+use crate::error::JooDockError;
+use crate::storage::{FileGroup, FileItem, Settings};
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Everything needed to rebuild a dock on another machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub version: u32,
+    #[serde(rename = "exportedAt")]
+    pub exported_at: String,
+    #[serde(rename = "appVersion")]
+    pub app_version: String,
+    pub files: Vec<FileItem>,
+    pub groups: Vec<FileGroup>,
+    pub settings: Settings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportMode {
+    /// Discard the current dock and use the bundle as-is.
+    Replace,
+    /// Add bundle groups and files next to the existing ones.
+    Merge,
+    /// Put every bundle file into one freshly created group.
+    MergeIntoNewGroup,
+}
+
+/// What an import changes, returned for both dry runs and real imports.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportPreview {
+    #[serde(rename = "groupsAdded")]
+    pub groups_added: Vec<String>,
+    #[serde(rename = "groupsMerged")]
+    pub groups_merged: Vec<String>,
+    #[serde(rename = "groupsRemoved")]
+    pub groups_removed: Vec<String>,
+    #[serde(rename = "filesAdded")]
+    pub files_added: Vec<String>,
+    #[serde(rename = "filesSkipped")]
+    pub files_skipped: Vec<String>,
+    #[serde(rename = "filesRemoved")]
+    pub files_removed: Vec<String>,
+    #[serde(rename = "settingsChanged")]
+    pub settings_changed: bool,
+}
+
+pub struct ImportPlan {
+    pub files: Vec<FileItem>,
+    pub groups: Vec<FileGroup>,
+    pub settings: Settings,
+    pub preview: ImportPreview,
+}
+
+impl ConfigBundle {
+    pub fn new(files: Vec<FileItem>, groups: Vec<FileGroup>, settings: Settings) -> Self {
+        ConfigBundle {
+            version: BUNDLE_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            files,
+            groups,
+            settings,
+        }
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, JooDockError> {
+        let bundle: ConfigBundle = serde_json::from_str(content)?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(JooDockError::InvalidInput(format!(
+                "Bundle version {} was created by a newer JooDock (supported: {})",
+                bundle.version, BUNDLE_VERSION
+            )));
+        }
+        Ok(bundle)
+    }
+}
+
+/// Computes the dock that results from importing `bundle` without touching disk.
+pub fn plan_import(
+    bundle: ConfigBundle,
+    files: Vec<FileItem>,
+    groups: Vec<FileGroup>,
+    settings: Settings,
+    mode: ImportMode,
+    new_group_name: Option<String>,
+) -> ImportPlan {
+    match mode {
+        ImportMode::Replace => plan_replace(bundle, files, groups, settings),
+        ImportMode::Merge => plan_merge(bundle, files, groups, settings),
+        ImportMode::MergeIntoNewGroup => {
+            plan_merge_into_new_group(bundle, files, groups, settings, new_group_name)
+        }
+    }
+}
+
+fn plan_replace(
+    bundle: ConfigBundle,
+    files: Vec<FileItem>,
+    groups: Vec<FileGroup>,
+    settings: Settings,
+) -> ImportPlan {
+    let preview = ImportPreview {
+        groups_added: bundle.groups.iter().map(|g| g.name.clone()).collect(),
+        groups_removed: groups.iter().map(|g| g.name.clone()).collect(),
+        files_added: bundle.files.iter().map(|f| f.path.clone()).collect(),
+        files_removed: files.iter().map(|f| f.path.clone()).collect(),
+        settings_changed: !same_settings(&settings, &bundle.settings),
+        ..Default::default()
+    };
+
+    ImportPlan {
+        files: bundle.files,
+        groups: bundle.groups,
+        settings: bundle.settings,
+        preview,
+    }
+}
+
+fn plan_merge(
+    bundle: ConfigBundle,
+    mut files: Vec<FileItem>,
+    mut groups: Vec<FileGroup>,
+    settings: Settings,
+) -> ImportPlan {
+    let mut preview = ImportPreview::default();
+    let mut group_ids: HashSet<String> = groups.iter().map(|g| g.id.clone()).collect();
+    // Bundle group id -> id of the group it ends up in locally
+    let mut group_map: HashMap<String, String> = HashMap::new();
+
+    // Parents before children, so a child's parent is always mapped already
+    let mut bundle_groups = bundle.groups;
+    let depths: HashMap<String, usize> = bundle_groups
+        .iter()
+        .map(|g| (g.id.clone(), depth(&bundle_groups, g)))
+        .collect();
+    bundle_groups.sort_by_key(|g| (depths[&g.id], g.sort_order));
+
+    for mut group in bundle_groups {
+        let parent_id = group
+            .parent_id
+            .as_ref()
+            .and_then(|id| group_map.get(id))
+            .cloned();
+        // Files merged into a smart group would be hidden, so only manual groups match
+        let existing = groups.iter().find(|g| {
+            g.parent_id == parent_id
+                && g.name.eq_ignore_ascii_case(&group.name)
+                && g.smart.is_none()
+                && group.smart.is_none()
+        });
+        if let Some(existing) = existing {
+            group_map.insert(group.id, existing.id.clone());
+            preview.groups_merged.push(existing.name.clone());
+            continue;
+        }
+
+        let original_id = group.id.clone();
+        if group_ids.contains(&group.id) {
+            group.id = uuid::Uuid::new_v4().to_string();
+        }
+        group.name = free_name(&groups, &parent_id, &group.name);
+        group.sort_order = groups.iter().filter(|g| g.parent_id == parent_id).count() as i32;
+        group.parent_id = parent_id;
+        group_ids.insert(group.id.clone());
+        group_map.insert(original_id, group.id.clone());
+        preview.groups_added.push(group.name.clone());
+        groups.push(group);
+    }
+
+    merge_files(&mut files, bundle.files, &mut preview, |group_id| {
+        group_id.and_then(|id| group_map.get(&id).cloned())
+    });
+
+    ImportPlan {
+        files,
+        groups,
+        settings,
+        preview,
+    }
+}
+
+fn plan_merge_into_new_group(
+    bundle: ConfigBundle,
+    mut files: Vec<FileItem>,
+    mut groups: Vec<FileGroup>,
+    settings: Settings,
+    new_group_name: Option<String>,
+) -> ImportPlan {
+    let mut preview = ImportPreview::default();
+    let name = new_group_name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("Imported {}", chrono::Local::now().format("%Y-%m-%d")));
+
+    let group = FileGroup {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        icon: "archive".to_string(),
+        sort_order: groups.iter().filter(|g| g.parent_id.is_none()).count() as i32,
+        parent_id: None,
+        smart: None,
+        is_expanded: true,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        color: None,
+    };
+    let group_id = group.id.clone();
+    preview.groups_added.push(group.name.clone());
+    groups.push(group);
+
+    merge_files(&mut files, bundle.files, &mut preview, |_| {
+        Some(group_id.clone())
+    });
+
+    ImportPlan {
+        files,
+        groups,
+        settings,
+        preview,
+    }
+}
+
+/// `name`, or `name (2)`, `name (3)`... if a sibling already uses it.
+fn free_name(groups: &[FileGroup], parent_id: &Option<String>, name: &str) -> String {
+    let taken = |name: &str| {
+        groups
+            .iter()
+            .any(|g| &g.parent_id == parent_id && g.name.eq_ignore_ascii_case(name))
+    };
+    (1..)
+        .map(|n| match n {
+            1 => name.to_string(),
+            n => format!("{} ({})", name, n),
+        })
+        .find(|name| !taken(name))
+        .unwrap_or_default()
+}
+
+/// Appends bundle files that are not pinned yet, remapping ids and groups.
+/// Archived items do not count as pinned.
+fn merge_files(
+    files: &mut Vec<FileItem>,
+    incoming: Vec<FileItem>,
+    preview: &mut ImportPreview,
+    map_group: impl Fn(Option<String>) -> Option<String>,
+) {
+    let mut file_ids: HashSet<String> = files.iter().map(|f| f.id.clone()).collect();
+
+    for mut file in incoming {
+        if files.iter().any(|f| !f.is_deleted() && f.path == file.path) {
+            preview.files_skipped.push(file.path);
+            continue;
+        }
+        if file_ids.contains(&file.id) {
+            file.id = uuid::Uuid::new_v4().to_string();
+        }
+        file.group_id = map_group(file.group_id.take());
+        file.sort_order = files::next_sort_order(files, &file.group_id);
+        file_ids.insert(file.id.clone());
+        preview.files_added.push(file.path.clone());
+        files.push(file);
+    }
+}
+
+/// Number of ancestors of `group` within `groups` (bounded to survive cycles).
+fn depth(groups: &[FileGroup], group: &FileGroup) -> usize {
+    let mut depth = 0;
+    let mut current = group.parent_id.as_ref();
+    while let Some(parent_id) = current {
+        if depth >= groups.len() {
+            break;
+        }
+        depth += 1;
+        current = groups
+            .iter()
+            .find(|g| &g.id == parent_id)
+            .and_then(|g| g.parent_id.as_ref());
+    }
+    depth
+}
+
+fn same_settings(a: &Settings, b: &Settings) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smart::SmartRule;
+
+    fn group(id: &str, name: &str, parent_id: Option<&str>) -> FileGroup {
+        FileGroup {
+            id: id.to_string(),
+            name: name.to_string(),
+            icon: "folder".to_string(),
+            sort_order: 0,
+            parent_id: parent_id.map(String::from),
+            smart: None,
+            is_expanded: true,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            color: None,
+        }
+    }
+
+    fn file(id: &str, path: &str, group_id: Option<&str>) -> FileItem {
+        let mut file = FileItem::new(
+            path.to_string(),
+            path.to_string(),
+            group_id.map(String::from),
+        );
+        file.id = id.to_string();
+        file
+    }
+
+    fn bundle(files: Vec<FileItem>, groups: Vec<FileGroup>) -> ConfigBundle {
+        ConfigBundle::new(files, groups, Settings::default())
+    }
+
+    fn find<'a>(files: &'a [FileItem], path: &str) -> &'a FileItem {
+        files.iter().find(|f| f.path == path).unwrap()
+    }
+
+    #[test]
+    fn merge_matches_groups_by_name_and_remaps_clashing_ids() {
+        let local_groups = vec![group("work", "Work", None)];
+        let local_files = vec![file("f1", "C:/a.pdf", Some("work"))];
+        // The bundle reuses ids that mean something else here
+        let incoming = bundle(
+            vec![
+                file("f1", "C:/b.pdf", Some("their-work")),
+                file("f2", "C:/c.pdf", Some("work")),
+                file("f3", "C:/a.pdf", None),
+            ],
+            vec![
+                group("their-work", "WORK", None),
+                group("work", "Trips", Some("their-work")),
+            ],
+        );
+
+        let plan = plan_import(
+            incoming,
+            local_files,
+            local_groups,
+            Settings::default(),
+            ImportMode::Merge,
+            None,
+        );
+
+        assert_eq!(plan.preview.groups_merged, ["Work"]);
+        assert_eq!(plan.preview.groups_added, ["Trips"]);
+        assert_eq!(plan.preview.files_skipped, ["C:/a.pdf"]);
+        let trips = plan.groups.iter().find(|g| g.name == "Trips").unwrap();
+        assert_ne!(trips.id, "work");
+        assert_eq!(trips.parent_id.as_deref(), Some("work"));
+
+        let b = find(&plan.files, "C:/b.pdf");
+        assert_ne!(b.id, "f1");
+        assert_eq!(b.group_id.as_deref(), Some("work"));
+        assert_eq!(
+            find(&plan.files, "C:/c.pdf").group_id,
+            Some(trips.id.clone())
+        );
+        assert_eq!(plan.files.len(), 3);
+    }
+
+    #[test]
+    fn merge_skips_smart_groups_and_archived_items() {
+        let mut recent = group("recent", "Recent", None);
+        recent.smart = Some(SmartRule::Search {
+            query: "pdf".to_string(),
+        });
+        let mut archived = file("f1", "C:/a.pdf", None);
+        archived.deleted_at = Some(chrono::Utc::now().to_rfc3339());
+        let incoming = bundle(
+            vec![file("f2", "C:/a.pdf", Some("theirs"))],
+            vec![group("theirs", "Recent", None)],
+        );
+
+        let plan = plan_import(
+            incoming,
+            vec![archived],
+            vec![recent],
+            Settings::default(),
+            ImportMode::Merge,
+            None,
+        );
+
+        assert!(plan.preview.groups_merged.is_empty());
+        assert_eq!(plan.preview.groups_added, ["Recent (2)"]);
+        assert_eq!(plan.preview.files_added, ["C:/a.pdf"]);
+        let added = plan.files.iter().find(|f| !f.is_deleted()).unwrap();
+        assert_ne!(added.group_id.as_deref(), Some("recent"));
+    }
+
+    #[test]
+    fn merge_into_new_group_collects_every_new_file() {
+        let incoming = bundle(
+            vec![
+                file("f2", "C:/b.pdf", Some("theirs")),
+                file("f3", "C:/a.pdf", None),
+            ],
+            vec![group("theirs", "Theirs", None)],
+        );
+
+        let plan = plan_import(
+            incoming,
+            vec![file("f1", "C:/a.pdf", None)],
+            vec![group("work", "Work", None)],
+            Settings::default(),
+            ImportMode::MergeIntoNewGroup,
+            Some(" From laptop ".to_string()),
+        );
+
+        assert_eq!(plan.preview.groups_added, ["From laptop"]);
+        assert_eq!(plan.preview.files_skipped, ["C:/a.pdf"]);
+        let new_group = plan
+            .groups
+            .iter()
+            .find(|g| g.name == "From laptop")
+            .unwrap();
+        assert_eq!(
+            find(&plan.files, "C:/b.pdf").group_id,
+            Some(new_group.id.clone())
+        );
+        assert_eq!(plan.groups.len(), 2);
+    }
+
+    #[test]
+    fn replace_reports_everything_it_drops() {
+        let mut settings = Settings::default();
+        settings.confirm_commands = false;
+        let incoming = ConfigBundle::new(
+            vec![file("f2", "C:/b.pdf", None)],
+            vec![group("theirs", "Theirs", None)],
+            settings,
+        );
+
+        let plan = plan_import(
+            incoming,
+            vec![file("f1", "C:/a.pdf", None)],
+            vec![group("work", "Work", None)],
+            Settings::default(),
+            ImportMode::Replace,
+            None,
+        );
+
+        assert_eq!(plan.preview.groups_removed, ["Work"]);
+        assert_eq!(plan.preview.files_removed, ["C:/a.pdf"]);
+        assert_eq!(plan.preview.files_added, ["C:/b.pdf"]);
+        assert!(plan.preview.settings_changed);
+        assert_eq!(plan.files.len(), 1);
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{self, FileGroup, FileItem};
+
+/// The files and groups of one profile, as a single value that can be
+/// diffed and patched.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Dock {
+    pub files: Vec<FileItem>,
+    pub groups: Vec<FileGroup>,
+}
+
+/// One entity-level edit to a `Dock`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum Change {
+    PutFile { file: FileItem },
+    DeleteFile { id: String },
+    PutGroup { group: FileGroup },
+    DeleteGroup { id: String },
+}
+
+impl Change {
+    /// `(entity, id)` of the record this change touches.
+    pub fn key(&self) -> (&'static str, &str) {
+        match self {
+            Change::PutFile { file } => ("file", &file.id),
+            Change::DeleteFile { id } => ("file", id),
+            Change::PutGroup { group } => ("group", &group.id),
+            Change::DeleteGroup { id } => ("group", id),
+        }
+    }
+}
+
+impl Dock {
+    /// Changes that turn `self` into `other`.
+    pub fn diff(&self, other: &Dock) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        for group in &other.groups {
+            if self.groups.iter().find(|g| g.id == group.id) != Some(group) {
+                changes.push(Change::PutGroup {
+                    group: group.clone(),
+                });
+            }
+        }
+        for file in &other.files {
+            if self.files.iter().find(|f| f.id == file.id) != Some(file) {
+                changes.push(Change::PutFile { file: file.clone() });
+            }
+        }
+        for file in &self.files {
+            if !other.files.iter().any(|f| f.id == file.id) {
+                changes.push(Change::DeleteFile {
+                    id: file.id.clone(),
+                });
+            }
+        }
+        for group in &self.groups {
+            if !other.groups.iter().any(|g| g.id == group.id) {
+                changes.push(Change::DeleteGroup {
+                    id: group.id.clone(),
+                });
+            }
+        }
+
+        changes
+    }
+
+    /// Applies a change in place, keeping the position of records that already exist.
+    pub fn apply(&mut self, change: Change) {
+        match change {
+            Change::PutFile { file } => match self.files.iter_mut().find(|f| f.id == file.id) {
+                Some(existing) => *existing = file,
+                None => self.files.push(file),
+            },
+            Change::DeleteFile { id } => self.files.retain(|f| f.id != id),
+            Change::PutGroup { group } => {
+                match self.groups.iter_mut().find(|g| g.id == group.id) {
+                    Some(existing) => *existing = group,
+                    None => self.groups.push(group),
+                }
+            }
+            Change::DeleteGroup { id } => self.groups.retain(|g| g.id != id),
+        }
+    }
+
+    /// Drops group and parent references that point at groups which no longer exist.
+    pub fn detach_orphans(&mut self) {
+        storage::normalize_groups(&mut self.groups);
+        let groups = &self.groups;
+        for file in self.files.iter_mut() {
+            if let Some(group_id) = &file.group_id {
+                if !groups.iter().any(|g| &g.id == group_id) {
+                    file.group_id = None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::commands::files;
+use crate::error::JooDockError;
+use crate::journal;
+use crate::metadata::{self, FileEntry};
+use crate::storage::{self, FileItem};
+
+/// Removed items that can still be restored, most recently removed first.
+#[tauri::command]
+pub fn get_archive() -> Result<Vec<FileEntry>, JooDockError> {
+    let mut archived: Vec<FileItem> = storage::load_files()?
+        .into_iter()
+        .filter(|f| f.is_deleted())
+        .collect();
+    archived.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(metadata::with_metadata(archived))
+}
+
+/// Puts an archived item back at the end of its group, or of the ungrouped
+/// items if the group is gone or has become a smart group. The item comes back
+/// without its expiry, and the inbox counts its age from the restore. Fails if
+/// its file was pinned again in the meantime.
+#[tauri::command]
+pub fn restore_file(id: String) -> Result<FileItem, JooDockError> {
+    journal::record("Restore file", || {
+        let groups = storage::load_groups()?;
+        let mut files = storage::load_files()?;
+        let index = files
+            .iter()
+            .position(|f| f.id == id && f.is_deleted())
+            .ok_or_else(|| JooDockError::not_found("archived file", &id))?;
+        if files[index].item_type.is_on_disk() {
+            let key = files::pinned_key(&files[index].path);
+            if files.iter().any(|f| {
+                !f.is_deleted() && f.item_type.is_on_disk() && files::pinned_key(&f.path) == key
+            }) {
+                return Err(JooDockError::duplicate("file", files[index].path.clone()));
+            }
+        }
+
+        let group_id = files[index]
+            .group_id
+            .clone()
+            .filter(|id| groups.iter().any(|g| &g.id == id && g.smart.is_none()));
+        files[index].sort_order = files::next_sort_order(&files, &group_id);
+        files[index].group_id = group_id;
+        files[index].deleted_at = None;
+        files[index].expires_at = None;
+        files[index].moved_at = Some(chrono::Utc::now().to_rfc3339());
+        let file = files[index].clone();
+        storage::save_files(&files)?;
+
+        Ok(file)
+    })
+}
+
+/// Deletes archived items for good: the given ids, or the whole archive when
+/// `ids` is `None`. Returns what was deleted.
+#[tauri::command]
+pub fn purge_archive(ids: Option<Vec<String>>) -> Result<Vec<FileItem>, JooDockError> {
+    journal::record("Empty archive", || {
+        let files = storage::load_files()?;
+        let (purged, kept): (Vec<FileItem>, Vec<FileItem>) = files
+            .into_iter()
+            .partition(|f| f.is_deleted() && ids.as_ref().is_none_or(|ids| ids.contains(&f.id)));
+        if !purged.is_empty() {
+            storage::save_files(&kept)?;
+        }
+
+        Ok(purged)
+    })
+}
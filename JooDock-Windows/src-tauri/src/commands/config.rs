@@ -0,0 +1,79 @@
+use crate::bundle::{self, ConfigBundle, ImportMode, ImportPreview};
+use crate::error::JooDockError;
+use crate::storage;
+use std::fs;
+
+#[tauri::command]
+pub fn export_config(path: String) -> Result<(), JooDockError> {
+    // The archive stays on this machine
+    let mut files = storage::load_files()?;
+    files.retain(|f| !f.is_deleted());
+    let bundle = ConfigBundle::new(
+        files,
+        storage::load_groups()?,
+        storage::load_settings()?,
+    );
+    let content = serde_json::to_string_pretty(&bundle)?;
+    fs::write(&path, content).map_err(|e| JooDockError::io(&path, e))
+}
+
+#[tauri::command]
+pub fn import_config(
+    path: String,
+    mode: ImportMode,
+    new_group_name: Option<String>,
+    dry_run: bool,
+) -> Result<ImportPreview, JooDockError> {
+    let content = fs::read_to_string(&path).map_err(|e| JooDockError::io(&path, e))?;
+    let bundle = ConfigBundle::from_json(&content)?;
+
+    let _lock = storage::lock();
+    let plan = bundle::plan_import(
+        bundle,
+        storage::load_files()?,
+        storage::load_groups()?,
+        storage::load_settings()?,
+        mode,
+        new_group_name,
+    );
+
+    if !dry_run {
+        storage::save_groups(&plan.groups)?;
+        storage::save_files(&plan.files)?;
+        if plan.preview.settings_changed {
+            storage::save_settings(&plan.settings)?;
+        }
+    }
+
+    Ok(plan.preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{FileItem, Settings};
+
+    /// The only test that uses the global store, pointed at a temp folder.
+    #[test]
+    fn dry_run_changes_nothing() {
+        let dir = std::env::temp_dir().join(format!("joodock-import-{}", uuid::Uuid::new_v4()));
+        storage::configure_data_dir([format!("--data-dir={}", dir.display())]);
+        assert_eq!(storage::get_data_dir().unwrap(), dir);
+
+        let file = FileItem::new("a.pdf".to_string(), "C:/a.pdf".to_string(), None);
+        let bundle = ConfigBundle::new(vec![file], Vec::new(), Settings::default());
+        let path = dir.join("bundle.json");
+        fs::write(&path, serde_json::to_string(&bundle).unwrap()).unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let preview = import_config(path.clone(), ImportMode::Merge, None, true).unwrap();
+        assert_eq!(preview.files_added, ["C:/a.pdf"]);
+        assert!(storage::load_files().unwrap().is_empty());
+
+        let imported = import_config(path, ImportMode::Merge, None, false).unwrap();
+        assert_eq!(imported.files_added, preview.files_added);
+        assert_eq!(storage::load_files().unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+
+use crate::commands::files;
+use crate::error::JooDockError;
+use crate::fileops::{self, ConflictPolicy, FileOperation};
+use crate::storage::{self, FileItem};
+
+/// Renames a pinned file or folder on disk and updates its item.
+#[tauri::command]
+pub fn rename_file_on_disk(
+    id: String,
+    new_name: String,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<FileItem, JooDockError> {
+    let new_name = fileops::validate_name(&new_name)?;
+    let _lock = storage::lock();
+    let (files, index) = load_pinned(&id)?;
+    let from = PathBuf::from(&files[index].path);
+    let target = from.with_file_name(&new_name);
+
+    relocate(files, index, target, on_conflict, |id, from, to| {
+        FileOperation::Rename { id, from, to }
+    })
+}
+
+/// Moves a pinned file or folder into `folder` and updates its item.
+#[tauri::command]
+pub fn move_file_on_disk(
+    id: String,
+    folder: String,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<FileItem, JooDockError> {
+    let folder = Path::new(&folder);
+    if !folder.is_dir() {
+        return Err(JooDockError::FileMissing(folder.to_path_buf()));
+    }
+    let _lock = storage::lock();
+    let (files, index) = load_pinned(&id)?;
+    let from = PathBuf::from(&files[index].path);
+    let name = from
+        .file_name()
+        .ok_or_else(|| JooDockError::InvalidInput(format!("{} cannot be moved", from.display())))?;
+    let target = folder.join(name);
+
+    relocate(files, index, target, on_conflict, |id, from, to| {
+        FileOperation::Move { id, from, to }
+    })
+}
+
+/// Copies a pinned file or folder next to itself (`name - Copy.ext`) and pins
+/// the copy right after the original.
+#[tauri::command]
+pub fn duplicate_file_on_disk(id: String) -> Result<FileItem, JooDockError> {
+    let _lock = storage::lock();
+    let (mut files, index) = load_pinned(&id)?;
+    let from = PathBuf::from(&files[index].path);
+    let target = fileops::free_path(&from, " - Copy");
+    fileops::copy_path(&from, &target)?;
+
+    let original = files[index].clone();
+    let mut copy = original.clone();
+    copy.id = uuid::Uuid::new_v4().to_string();
+    copy.added_at = chrono::Utc::now().to_rfc3339();
+    copy.last_accessed_at = None;
+    copy.set_path(target.to_string_lossy().to_string());
+    copy.sort_order = files::next_sort_order(&files, &copy.group_id);
+    files.push(copy.clone());
+    let copy = files::move_file_in(
+        &mut files,
+        &copy.id,
+        original.group_id,
+        original.sort_order as usize + 1,
+    )?;
+
+    if let Err(e) = storage::save_files(&files) {
+        let _ = fileops::trash(&target);
+        return Err(e);
+    }
+    push_operation(FileOperation::Duplicate {
+        id: copy.id.clone(),
+        path: copy.path.clone(),
+    })?;
+
+    Ok(copy)
+}
+
+/// Sends a pinned file or folder to the recycle bin and unpins it.
+#[tauri::command]
+pub fn trash_file(id: String) -> Result<(), JooDockError> {
+    let _lock = storage::lock();
+    let (mut files, index) = load_pinned(&id)?;
+    let file = files.remove(index);
+    let path = PathBuf::from(&file.path);
+    fileops::trash(&path)?;
+
+    files::renumber_group(&mut files, &file.group_id);
+    if let Err(e) = storage::save_files(&files) {
+        let _ = fileops::restore_from_trash(&path);
+        return Err(e);
+    }
+    push_operation(FileOperation::Trash { file })
+}
+
+/// Recent on-disk operations, newest last.
+#[tauri::command]
+pub fn get_file_operations() -> Result<Vec<FileOperation>, JooDockError> {
+    storage::load_file_operations()
+}
+
+/// Reverts the newest on-disk operation and returns it, or `None` if there is
+/// nothing to undo. A file replaced through `ConflictPolicy::Replace` stays in
+/// the recycle bin.
+#[tauri::command]
+pub fn undo_file_operation() -> Result<Option<FileOperation>, JooDockError> {
+    let _lock = storage::lock();
+    let mut operations = storage::load_file_operations()?;
+    let Some(operation) = operations.last().cloned() else {
+        return Ok(None);
+    };
+
+    let mut files = storage::load_files()?;
+    match &operation {
+        FileOperation::Rename { id, from, to } | FileOperation::Move { id, from, to } => {
+            if Path::new(from).exists() && !fileops::same_path(Path::new(from), Path::new(to)) {
+                return Err(JooDockError::duplicate("file", from));
+            }
+            fileops::move_path(Path::new(to), Path::new(from))?;
+            if let Some(file) = files.iter_mut().find(|f| &f.id == id && &f.path == to) {
+                file.set_path(from.clone());
+            }
+        }
+        FileOperation::Duplicate { id, path } => {
+            fileops::trash(Path::new(path))?;
+            if let Some(group_id) = files.iter().find(|f| &f.id == id).map(|f| f.group_id.clone()) {
+                files.retain(|f| &f.id != id);
+                files::renumber_group(&mut files, &group_id);
+            }
+        }
+        FileOperation::Trash { file } => {
+            fileops::restore_from_trash(Path::new(&file.path))?;
+            if !files.iter().any(|f| f.path == file.path) {
+                let groups = storage::load_groups()?;
+                let group_id = file
+                    .group_id
+                    .clone()
+                    .filter(|id| groups.iter().any(|g| &g.id == id && g.smart.is_none()));
+                files.push(file.clone());
+                files::move_file_in(&mut files, &file.id, group_id, file.sort_order as usize)?;
+            }
+        }
+    }
+    storage::save_files(&files)?;
+
+    operations.pop();
+    storage::save_file_operations(&operations)?;
+
+    Ok(Some(operation))
+}
+
+/// The pinned files and the index of `id`, which must be a file or folder that exists.
+fn load_pinned(id: &str) -> Result<(Vec<FileItem>, usize), JooDockError> {
+    let files = storage::load_files()?;
+    let index = files
+        .iter()
+        .position(|f| f.id == id && !f.is_deleted())
+        .ok_or_else(|| JooDockError::not_found("file", id))?;
+    if !files[index].item_type.is_on_disk() {
+        return Err(JooDockError::InvalidInput(format!(
+            "{} is not a file or folder",
+            files[index].name
+        )));
+    }
+    let path = Path::new(&files[index].path);
+    if !path.exists() {
+        return Err(JooDockError::FileMissing(path.to_path_buf()));
+    }
+    Ok((files, index))
+}
+
+/// Moves the file at `files[index]` to `target` and saves the updated item,
+/// moving the file back if the item cannot be saved.
+fn relocate(
+    mut files: Vec<FileItem>,
+    index: usize,
+    target: PathBuf,
+    on_conflict: Option<ConflictPolicy>,
+    operation: impl FnOnce(String, String, String) -> FileOperation,
+) -> Result<FileItem, JooDockError> {
+    let from = PathBuf::from(&files[index].path);
+    if target == from {
+        return Ok(files[index].clone());
+    }
+    // Never replace a file that another item points at
+    let policy = on_conflict.unwrap_or_default();
+    let pinned_elsewhere = files
+        .iter()
+        .any(|f| Path::new(&f.path) == target && f.id != files[index].id);
+    if pinned_elsewhere && policy != ConflictPolicy::KeepBoth {
+        return Err(JooDockError::duplicate("file", target.to_string_lossy()));
+    }
+    let target = fileops::resolve_target(&from, target, policy)?;
+    let to = target.to_string_lossy().to_string();
+
+    fileops::move_path(&from, &target)?;
+    files[index].set_path(to.clone());
+    if let Err(e) = storage::save_files(&files) {
+        let _ = fileops::move_path(&target, &from);
+        return Err(e);
+    }
+
+    let file = files[index].clone();
+    push_operation(operation(
+        file.id.clone(),
+        from.to_string_lossy().to_string(),
+        to,
+    ))?;
+
+    Ok(file)
+}
+
+fn push_operation(operation: FileOperation) -> Result<(), JooDockError> {
+    let mut operations = storage::load_file_operations()?;
+    operations.push(operation);
+    storage::save_file_operations(&operations)
+}
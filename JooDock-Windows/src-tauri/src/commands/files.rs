@@ -1,71 +1,489 @@
-use crate::storage::{self, FileItem};
+use crate::commands::groups;
+use crate::display;
+use crate::error::JooDockError;
+use crate::fileops;
+use crate::health::Fingerprint;
+use crate::journal;
+use crate::launch::{self, Launch};
+use crate::metadata::{self, FileEntry};
+use crate::preview::{self, Preview};
+use crate::rules;
+use crate::storage::{self, FileItem, FileKind, ItemType};
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use tauri_plugin_shell::ShellExt;
 
+const DIRECTORY_LISTING_LIMIT: usize = 500;
+
+/// Pinned files with `kind` re-checked against the disk and their metadata
+/// attached. The refreshed kinds are not written back, so a file missing on
+/// one synced device does not show up as a change on the others.
+#[tauri::command]
+pub fn get_files() -> Result<Vec<FileEntry>, JooDockError> {
+    let mut files = storage::load_files()?;
+    files.retain(|f| !f.is_deleted());
+    for file in files.iter_mut().filter(|f| f.item_type.is_on_disk()) {
+        file.kind = FileKind::of(Path::new(&file.path));
+    }
+    Ok(metadata::with_metadata(files))
+}
+
+/// What happened to one of the paths passed to `add_files`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum AddResult {
+    Added {
+        path: String,
+        file: FileItem,
+    },
+    /// Already pinned, or given twice; `existing` is the id of the pinned item
+    Duplicate {
+        path: String,
+        existing: String,
+    },
+    Missing {
+        path: String,
+    },
+    /// The path could not be resolved, e.g. access was denied
+    Failed {
+        path: String,
+        error: JooDockError,
+    },
+}
+
 #[tauri::command]
-pub fn get_files() -> Vec<FileItem> {
-    storage::load_files()
+pub fn add_file(path: String, group_id: Option<String>) -> Result<FileItem, JooDockError> {
+    journal::record("Add file", || {
+        match pin_paths(vec![path], group_id)?.remove(0) {
+            AddResult::Added { file, .. } => Ok(file),
+            AddResult::Duplicate { path, .. } => Err(JooDockError::duplicate("file", path)),
+            AddResult::Missing { path } => Err(JooDockError::FileMissing(path.into())),
+            AddResult::Failed { error, .. } => Err(error),
+        }
+    })
 }
 
+/// Pins several files and folders at once, e.g. from a drop, saving once.
+/// Results are in the order of `paths`.
 #[tauri::command]
-pub fn add_file(path: String, group_id: Option<String>) -> Result<FileItem, String> {
-    let path_obj = Path::new(&path);
-    if !path_obj.exists() {
-        return Err("File does not exist".to_string());
+pub fn add_files(
+    paths: Vec<String>,
+    group_id: Option<String>,
+) -> Result<Vec<AddResult>, JooDockError> {
+    journal::record("Add files", || pin_paths(paths, group_id))
+}
+
+/// Pins every path that exists and is not pinned yet. Paths are canonicalized
+/// first, so `C:\Docs\a.pdf` and `c:/docs/../docs/A.pdf` count as one file.
+fn pin_paths(paths: Vec<String>, group_id: Option<String>) -> Result<Vec<AddResult>, JooDockError> {
+    if let Some(group_id) = &group_id {
+        groups::ensure_pinnable(group_id)?;
+    }
+    let rules = storage::load_rules()?;
+    let groups = storage::load_groups()?;
+    let mut files = storage::load_files()?;
+
+    // Links and commands only name a path; pinning that file is no duplicate
+    let mut pinned: HashMap<String, String> = files
+        .iter()
+        .filter(|f| !f.is_deleted() && f.item_type.is_on_disk())
+        .map(|f| (pinned_key(&f.path), f.id.clone()))
+        .collect();
+
+    let mut results = Vec::with_capacity(paths.len());
+    for path in paths {
+        let canonical = match fileops::canonical_path(Path::new(&path)) {
+            Ok(canonical) => canonical,
+            Err(JooDockError::FileMissing(_)) => {
+                results.push(AddResult::Missing { path });
+                continue;
+            }
+            Err(error) => {
+                results.push(AddResult::Failed { path, error });
+                continue;
+            }
+        };
+        if let Some(existing) = pinned.get(&fileops::path_key(&canonical)) {
+            let existing = existing.clone();
+            results.push(AddResult::Duplicate { path, existing });
+            continue;
+        }
+
+        let path_obj = Path::new(&canonical);
+        let name = path_obj
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| canonical.clone());
+        // Let the user's routing rules pick a group
+        let group_id = group_id
+            .clone()
+            .or_else(|| rules::route(&canonical, &rules, &groups));
+
+        let fingerprint = Fingerprint::of(path_obj);
+        let mut file = FileItem::new(name, canonical.clone(), group_id);
+        file.sort_order = next_sort_order(&files, &file.group_id);
+        file.fingerprint = fingerprint;
+
+        pinned.insert(fileops::path_key(&canonical), file.id.clone());
+        files.push(file.clone());
+        results.push(AddResult::Added { path, file });
     }
 
-    let name = path_obj
-        .file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| path.clone());
+    if results.iter().any(|r| matches!(r, AddResult::Added { .. })) {
+        storage::save_files(&files)?;
+    }
+    Ok(results)
+}
 
-    let mut files = storage::load_files();
+/// Key that tells whether two pinned paths are the same file. Pinned items may
+/// predate canonical paths, so they are resolved like new paths are.
+pub fn pinned_key(path: &str) -> String {
+    let path = fileops::canonical_path(Path::new(path)).unwrap_or_else(|_| path.to_string());
+    fileops::path_key(&path)
+}
 
-    // Check for duplicates
-    if files.iter().any(|f| f.path == path) {
-        return Err("File already exists".to_string());
+/// Pins a web link, `mailto:` address or other link opened by its handler.
+#[tauri::command]
+pub fn add_url(
+    url: String,
+    name: Option<String>,
+    group_id: Option<String>,
+) -> Result<FileItem, JooDockError> {
+    let url = launch::validate_url(&url)?;
+    let name = name.unwrap_or_else(|| launch::url_name(&url));
+    journal::record("Add link", || {
+        pin_item(FileItem::new(name, url, group_id), ItemType::Url, None)
+    })
+}
+
+/// Pins a program or script to run with `args` in `working_dir`.
+#[tauri::command]
+pub fn add_command(
+    program: String,
+    args: Vec<String>,
+    working_dir: Option<String>,
+    name: Option<String>,
+    group_id: Option<String>,
+) -> Result<FileItem, JooDockError> {
+    let launch = Launch { args, working_dir };
+    launch::validate_command(&program, &launch)?;
+    let name = name.unwrap_or_else(|| {
+        Path::new(&program)
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| program.clone())
+    });
+    journal::record("Add command", || {
+        pin_item(
+            FileItem::new(name, program, group_id),
+            ItemType::Command,
+            Some(launch),
+        )
+    })
+}
+
+/// Pins a link or command, which is a duplicate only if it opens the same thing.
+fn pin_item(
+    mut item: FileItem,
+    item_type: ItemType,
+    launch: Option<Launch>,
+) -> Result<FileItem, JooDockError> {
+    if let Some(group_id) = &item.group_id {
+        groups::ensure_pinnable(group_id)?;
+    }
+    let mut files = storage::load_files()?;
+    if files.iter().any(|f| {
+        !f.is_deleted() && f.item_type == item_type && f.path == item.path && f.launch == launch
+    }) {
+        return Err(JooDockError::duplicate("item", item.path));
     }
 
-    let file = FileItem {
-        id: uuid::Uuid::new_v4().to_string(),
-        name,
-        path,
-        group_id,
-        added_at: chrono::Utc::now().to_rfc3339(),
-        last_accessed_at: None,
+    item.item_type = item_type;
+    item.kind = FileKind::File;
+    item.launch = launch;
+    item.sort_order = next_sort_order(&files, &item.group_id);
+    files.push(item.clone());
+    storage::save_files(&files)?;
+
+    Ok(item)
+}
+
+/// Moves a pinned item to the archive, from where it can be restored until
+/// the retention period runs out.
+#[tauri::command]
+pub fn remove_file(id: String) -> Result<(), JooDockError> {
+    journal::record("Remove file", || {
+        let mut files = storage::load_files()?;
+        if archive_file_in(&mut files, &id) {
+            storage::save_files(&files)?;
+        }
+        Ok(())
+    })
+}
+
+/// Marks an item as deleted and closes the gap in its group. Returns `false`
+/// if there is no such item or it is archived already.
+pub fn archive_file_in(files: &mut [FileItem], id: &str) -> bool {
+    let Some(file) = files.iter_mut().find(|f| f.id == id && !f.is_deleted()) else {
+        return false;
     };
+    file.deleted_at = Some(chrono::Utc::now().to_rfc3339());
+    let group_id = file.group_id.clone();
+    renumber_group(files, &group_id);
+    true
+}
 
-    files.push(file.clone());
+#[tauri::command]
+pub fn move_file(
+    id: String,
+    group_id: Option<String>,
+    index: usize,
+) -> Result<FileItem, JooDockError> {
+    journal::record("Move file", || {
+        if let Some(group_id) = &group_id {
+            groups::ensure_pinnable(group_id)?;
+        }
+
+        let mut files = storage::load_files()?;
+        let file = move_file_in(&mut files, &id, group_id, index)?;
+        storage::save_files(&files)?;
+
+        Ok(file)
+    })
+}
+
+/// Shows `alias` instead of the file name; `None` or an empty alias restores it.
+#[tauri::command]
+pub fn set_alias(id: String, alias: Option<String>) -> Result<FileItem, JooDockError> {
+    let alias = display::validate_alias(alias)?;
+    journal::record("Rename item", || {
+        update_file(&id, |file| file.alias = alias)
+    })
+}
+
+#[tauri::command]
+pub fn set_file_color(id: String, color: Option<String>) -> Result<FileItem, JooDockError> {
+    let color = color.map(|c| display::validate_color(&c)).transpose()?;
+    journal::record("Color item", || update_file(&id, |file| file.color = color))
+}
+
+/// Makes a pin temporary: the janitor clears it once `expires_at` (RFC 3339)
+/// has passed. `None` makes it permanent again.
+#[tauri::command]
+pub fn set_expiry(id: String, expires_at: Option<String>) -> Result<FileItem, JooDockError> {
+    let expires_at = expires_at
+        .map(|at| {
+            chrono::DateTime::parse_from_rfc3339(&at)
+                .map(|at| at.with_timezone(&chrono::Utc).to_rfc3339())
+                .map_err(|e| JooDockError::InvalidInput(format!("{} is not a date: {}", at, e)))
+        })
+        .transpose()?;
+    journal::record("Set expiry", || {
+        update_file(&id, |file| file.expires_at = expires_at)
+    })
+}
+
+/// Applies `edit` to a pinned item and saves it. Archived items are left alone.
+pub fn update_file(id: &str, edit: impl FnOnce(&mut FileItem)) -> Result<FileItem, JooDockError> {
+    let mut files = storage::load_files()?;
+    let file = files
+        .iter_mut()
+        .find(|f| f.id == id && !f.is_deleted())
+        .ok_or_else(|| JooDockError::not_found("file", id))?;
+    edit(file);
+    let file = file.clone();
     storage::save_files(&files)?;
 
     Ok(file)
 }
 
+/// Moves a file to `index` within `group_id` (clamped to the end of the group)
+/// and renumbers `sort_order` in both the old and the new group. Archived items
+/// cannot be moved.
+pub fn move_file_in(
+    files: &mut [FileItem],
+    id: &str,
+    group_id: Option<String>,
+    index: usize,
+) -> Result<FileItem, JooDockError> {
+    let moved = files
+        .iter()
+        .position(|f| f.id == id && !f.is_deleted())
+        .ok_or_else(|| JooDockError::not_found("file", id))?;
+    let source_group = files[moved].group_id.clone();
+
+    let mut order = ordered_in_group(files, &group_id);
+    order.retain(|&i| i != moved);
+    order.insert(index.min(order.len()), moved);
+
+    files[moved].set_group(group_id);
+    for (position, &i) in order.iter().enumerate() {
+        files[i].sort_order = position as i32;
+    }
+    if source_group != files[moved].group_id {
+        renumber_group(files, &source_group);
+    }
+
+    Ok(files[moved].clone())
+}
+
+/// Indexes of the files in a group, in display order. Archived items are left out.
+pub fn ordered_in_group(files: &[FileItem], group_id: &Option<String>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..files.len())
+        .filter(|&i| &files[i].group_id == group_id && !files[i].is_deleted())
+        .collect();
+    order.sort_by_key(|&i| files[i].sort_order);
+    order
+}
+
+pub fn renumber_group(files: &mut [FileItem], group_id: &Option<String>) {
+    for (position, i) in ordered_in_group(files, group_id).into_iter().enumerate() {
+        files[i].sort_order = position as i32;
+    }
+}
+
+pub fn next_sort_order(files: &[FileItem], group_id: &Option<String>) -> i32 {
+    files
+        .iter()
+        .filter(|f| &f.group_id == group_id && !f.is_deleted())
+        .map(|f| f.sort_order + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The first children of a folder, as returned by `list_directory`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryListing {
+    pub entries: Vec<FileItem>,
+    /// Whether the folder has more children than `entries` holds
+    pub truncated: bool,
+}
+
+/// Children of a directory for browsing a pinned folder in the popup:
+/// folders (including linked ones) first, then files, each sorted by name.
+#[tauri::command]
+pub fn list_directory(path: String) -> Result<DirectoryListing, JooDockError> {
+    let dir = Path::new(&path);
+    match FileKind::of(dir) {
+        FileKind::Missing => return Err(JooDockError::FileMissing(dir.to_path_buf())),
+        _ if !dir.is_dir() => {
+            return Err(JooDockError::InvalidInput(format!(
+                "{} is not a folder",
+                path
+            )))
+        }
+        _ => {}
+    }
+
+    // Sort every child before cutting the listing off, so the cut is predictable
+    let mut children: Vec<(bool, String, String)> = fs::read_dir(dir)
+        .map_err(|e| JooDockError::io(dir, e))?
+        .filter_map(|e| e.ok())
+        .map(|entry| {
+            let path = entry.path();
+            let is_dir = match entry.file_type() {
+                Ok(file_type) if !file_type.is_symlink() => file_type.is_dir(),
+                _ => path.is_dir(),
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            (is_dir, name, path.to_string_lossy().to_string())
+        })
+        .collect();
+    children.sort_by_cached_key(|(is_dir, name, _)| (!is_dir, name.to_lowercase()));
+
+    let truncated = children.len() > DIRECTORY_LISTING_LIMIT;
+    let entries = children
+        .into_iter()
+        .take(DIRECTORY_LISTING_LIMIT)
+        .enumerate()
+        .map(|(position, (_, name, path))| {
+            let mut child = FileItem::new(name, path, None);
+            child.sort_order = position as i32;
+            child
+        })
+        .collect();
+
+    Ok(DirectoryListing { entries, truncated })
+}
+
+/// Bounded preview of a file or folder for the hover pane.
 #[tauri::command]
-pub fn remove_file(id: String) -> Result<(), String> {
-    let mut files = storage::load_files();
-    files.retain(|f| f.id != id);
-    storage::save_files(&files)
+pub async fn preview_file(path: String) -> Result<Preview, JooDockError> {
+    preview::preview(Path::new(&path))
 }
 
+/// Opens a pinned item (found by `id`, else by `path`) or any other path.
+/// Commands, and links that start other apps, only open with `confirmed` set
+/// while `confirmCommands` is on.
 #[tauri::command]
-pub async fn open_file(app: tauri::AppHandle, path: String) -> Result<(), String> {
+pub async fn open_file(
+    app: tauri::AppHandle,
+    path: String,
+    id: Option<String>,
+    confirmed: Option<bool>,
+) -> Result<(), JooDockError> {
+    let _lock = storage::lock();
+    let mut files = storage::load_files()?;
+    // Several commands can run the same program, so the id wins when given
+    let index = id
+        .and_then(|id| files.iter().position(|f| f.id == id && !f.is_deleted()))
+        .or_else(|| files.iter().position(|f| f.path == path && !f.is_deleted()));
+
+    match index.map(|i| &files[i]) {
+        Some(item) => {
+            if item.item_type == ItemType::Command {
+                ensure_confirmed(format!("Run {}?", item.name), confirmed)?;
+            }
+            if item.item_type == ItemType::Url && launch::needs_confirmation(&item.path) {
+                ensure_confirmed(format!("Open {}?", item.path), confirmed)?;
+            }
+            launch_item(&app, item)?;
+        }
+        None => {
+            if launch::needs_confirmation(&path) {
+                ensure_confirmed(format!("Open {}?", path), confirmed)?;
+            }
+            open_with_shell(&app, &path)?;
+        }
+    }
+
     // Update last accessed time
-    let mut files = storage::load_files();
-    if let Some(file) = files.iter_mut().find(|f| f.path == path) {
-        file.last_accessed_at = Some(chrono::Utc::now().to_rfc3339());
-        let _ = storage::save_files(&files);
+    if let Some(i) = index {
+        files[i].last_accessed_at = Some(chrono::Utc::now().to_rfc3339());
+        storage::save_files(&files)?;
     }
+    storage::record_open(&path);
+    Ok(())
+}
+
+/// Opens an item without asking: runs a command, or hands a file, folder or
+/// link to its default application.
+pub fn launch_item(app: &tauri::AppHandle, item: &FileItem) -> Result<(), JooDockError> {
+    match item.item_type {
+        ItemType::Command => launch::run(&item.path, &item.launch.clone().unwrap_or_default()),
+        ItemType::File | ItemType::Folder | ItemType::Url => open_with_shell(app, &item.path),
+    }
+}
 
-    // Open file with default application
+/// Errors with `prompt` as a `ConfirmationRequired` unless the user confirmed
+/// or turned the `confirmCommands` setting off.
+pub fn ensure_confirmed(prompt: String, confirmed: Option<bool>) -> Result<(), JooDockError> {
+    if confirmed != Some(true) && storage::load_settings()?.confirm_commands {
+        return Err(JooDockError::ConfirmationRequired(prompt));
+    }
+    Ok(())
+}
+
+fn open_with_shell(app: &tauri::AppHandle, path: &str) -> Result<(), JooDockError> {
     app.shell()
-        .open(&path, None)
-        .map_err(|e| e.to_string())
+        .open(path, None)
+        .map_err(|e| JooDockError::Shell(e.to_string()))
 }
 
 #[tauri::command]
-pub fn get_recent_files() -> Vec<FileItem> {
+pub fn get_recent_files() -> Vec<FileEntry> {
     let mut recent_files = Vec::new();
 
     #[cfg(windows)]
@@ -111,14 +529,11 @@ pub fn get_recent_files() -> Vec<FileItem> {
                             .unwrap_or_default();
 
                         if !name.is_empty() && !name.starts_with('.') {
-                            recent_files.push(FileItem {
-                                id: uuid::Uuid::new_v4().to_string(),
+                            recent_files.push(FileItem::new(
                                 name,
-                                path: path.to_string_lossy().to_string(),
-                                group_id: None,
-                                added_at: chrono::Utc::now().to_rfc3339(),
-                                last_accessed_at: None,
-                            });
+                                path.to_string_lossy().to_string(),
+                                None,
+                            ));
                         }
                     }
                 }
@@ -126,5 +541,106 @@ pub fn get_recent_files() -> Vec<FileItem> {
         }
     }
 
-    recent_files
+    metadata::with_metadata(recent_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, group_id: Option<&str>, sort_order: i32) -> FileItem {
+        let mut file = FileItem::new(
+            name.to_string(),
+            format!("C:\\Docs\\{}", name),
+            group_id.map(String::from),
+        );
+        file.sort_order = sort_order;
+        file
+    }
+
+    fn dock() -> Vec<FileItem> {
+        vec![
+            item("a", Some("work"), 0),
+            item("b", Some("work"), 1),
+            item("c", Some("work"), 2),
+            item("d", Some("home"), 0),
+            item("e", Some("home"), 1),
+            item("x", None, 0),
+        ]
+    }
+
+    fn id_of(files: &[FileItem], name: &str) -> String {
+        files.iter().find(|f| f.name == name).unwrap().id.clone()
+    }
+
+    /// Names in a group with their `sort_order`, in display order.
+    fn group(files: &[FileItem], group_id: Option<&str>) -> Vec<(String, i32)> {
+        ordered_in_group(files, &group_id.map(String::from))
+            .into_iter()
+            .map(|i| (files[i].name.clone(), files[i].sort_order))
+            .collect()
+    }
+
+    fn names(order: &[(&str, i32)]) -> Vec<(String, i32)> {
+        order.iter().map(|&(n, o)| (n.to_string(), o)).collect()
+    }
+
+    #[test]
+    fn moves_across_groups() {
+        let mut files = dock();
+        let id = id_of(&files, "b");
+        let moved = move_file_in(&mut files, &id, Some("home".to_string()), 1).unwrap();
+
+        assert_eq!(moved.group_id.as_deref(), Some("home"));
+        assert_eq!(
+            group(&files, Some("home")),
+            names(&[("d", 0), ("b", 1), ("e", 2)])
+        );
+        assert_eq!(group(&files, Some("work")), names(&[("a", 0), ("c", 1)]));
+    }
+
+    #[test]
+    fn moves_to_ungrouped() {
+        let mut files = dock();
+        let id = id_of(&files, "a");
+        let moved = move_file_in(&mut files, &id, None, 0).unwrap();
+
+        assert_eq!(moved.group_id, None);
+        assert_eq!(group(&files, None), names(&[("a", 0), ("x", 1)]));
+        assert_eq!(group(&files, Some("work")), names(&[("b", 0), ("c", 1)]));
+    }
+
+    #[test]
+    fn clamps_out_of_range_indexes() {
+        let mut files = dock();
+        let id = id_of(&files, "a");
+        move_file_in(&mut files, &id, Some("work".to_string()), 99).unwrap();
+        assert_eq!(
+            group(&files, Some("work")),
+            names(&[("b", 0), ("c", 1), ("a", 2)])
+        );
+
+        move_file_in(&mut files, &id, Some("home".to_string()), usize::MAX).unwrap();
+        assert_eq!(
+            group(&files, Some("home")),
+            names(&[("d", 0), ("e", 1), ("a", 2)])
+        );
+        assert_eq!(group(&files, Some("work")), names(&[("b", 0), ("c", 1)]));
+    }
+
+    #[test]
+    fn rejects_unknown_ids() {
+        let mut files = dock();
+        assert!(move_file_in(&mut files, "missing", None, 0).is_err());
+    }
+
+    #[test]
+    fn rejects_archived_items() {
+        let mut files = dock();
+        let id = id_of(&files, "b");
+        archive_file_in(&mut files, &id);
+
+        assert!(move_file_in(&mut files, &id, Some("home".to_string()), 0).is_err());
+        assert_eq!(group(&files, Some("home")), names(&[("d", 0), ("e", 1)]));
+    }
 }
@@ -1,60 +1,526 @@
-use crate::storage::{self, FileGroup, FileItem};
+use serde::Serialize;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::commands::files;
+use crate::display;
+use crate::error::JooDockError;
+use crate::journal;
+use crate::launch;
+use crate::smart::{self, SmartRule};
+use crate::storage::{self, FileGroup, FileItem, ItemType};
+
+/// A group together with its sub-groups, as returned by `get_groups`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupNode {
+    #[serde(flatten)]
+    pub group: FileGroup,
+    pub children: Vec<GroupNode>,
+}
 
 #[tauri::command]
-pub fn get_groups() -> Vec<FileGroup> {
-    storage::load_groups()
+pub fn get_groups() -> Result<Vec<GroupNode>, JooDockError> {
+    Ok(build_tree(&storage::load_groups()?, None))
 }
 
 #[tauri::command]
-pub fn add_group(name: String, icon: String) -> Result<FileGroup, String> {
-    let mut groups = storage::load_groups();
+pub fn add_group(
+    name: String,
+    icon: String,
+    parent_id: Option<String>,
+    smart: Option<SmartRule>,
+) -> Result<FileGroup, JooDockError> {
+    journal::record("Add group", || {
+        if let Some(rule) = &smart {
+            rule.validate()?;
+        }
 
-    let group = FileGroup {
-        id: uuid::Uuid::new_v4().to_string(),
-        name,
-        icon,
-        sort_order: groups.len() as i32,
-        is_expanded: true,
-        created_at: chrono::Utc::now().to_rfc3339(),
-    };
+        let mut groups = storage::load_groups()?;
+        if let Some(parent_id) = &parent_id {
+            if !groups.iter().any(|g| &g.id == parent_id) {
+                return Err(JooDockError::not_found("group", parent_id));
+            }
+        }
+        let name = validate_group_name(&groups, &name, &parent_id, None)?;
+
+        let group = FileGroup {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            icon,
+            sort_order: children_of(&groups, &parent_id).len() as i32,
+            parent_id,
+            smart,
+            is_expanded: true,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            color: None,
+        };
+
+        groups.push(group.clone());
+        storage::save_groups(&groups)?;
+
+        Ok(group)
+    })
+}
+
+/// Removes a group. Its files move to the parent group (or to ungrouped for a
+/// top-level group). Sub-groups move up to the parent as well, unless
+/// `recursive` is set, in which case the whole subtree is removed and the
+/// files of every removed group move to the parent.
+#[tauri::command]
+pub fn remove_group(id: String, recursive: Option<bool>) -> Result<(), JooDockError> {
+    journal::record("Remove group", || {
+        let mut groups = storage::load_groups()?;
+        let parent_id = groups
+            .iter()
+            .find(|g| g.id == id)
+            .ok_or_else(|| JooDockError::not_found("group", &id))?
+            .parent_id
+            .clone();
+
+        let mut removed = vec![id.clone()];
+        if recursive.unwrap_or(false) {
+            removed.extend(descendants(&groups, &id));
+        } else {
+            // Lift direct children to the parent, after its existing children
+            let mut next = children_of(&groups, &parent_id).len() as i32;
+            for i in children_of(&groups, &Some(id.clone())) {
+                groups[i].parent_id = parent_id.clone();
+                groups[i].sort_order = next;
+                next += 1;
+            }
+        }
+
+        // Move files to the end of the parent group, keeping their order; a
+        // smart parent would hide them, so they become ungrouped instead
+        let target = parent_id
+            .clone()
+            .filter(|p| groups.iter().any(|g| &g.id == p && g.smart.is_none()));
+        let mut files = storage::load_files()?;
+        for group_id in &removed {
+            move_group_files(&mut files, group_id, &target);
+        }
+        storage::save_files(&files)?;
+
+        // Remove groups and close the gaps in sort order
+        groups.retain(|g| !removed.contains(&g.id));
+        storage::normalize_groups(&mut groups);
+        storage::save_groups(&groups)
+    })
+}
+
+/// Moves a group (with its subtree) under `parent_id` at `index` among its new siblings.
+#[tauri::command]
+pub fn move_group(
+    id: String,
+    parent_id: Option<String>,
+    index: usize,
+) -> Result<FileGroup, JooDockError> {
+    journal::record("Move group", || {
+        let mut groups = storage::load_groups()?;
+        let moved = groups
+            .iter()
+            .position(|g| g.id == id)
+            .ok_or_else(|| JooDockError::not_found("group", &id))?;
+
+        if let Some(parent_id) = &parent_id {
+            if !groups.iter().any(|g| &g.id == parent_id) {
+                return Err(JooDockError::not_found("group", parent_id));
+            }
+            if parent_id == &id || descendants(&groups, &id).contains(parent_id) {
+                return Err(JooDockError::InvalidInput(
+                    "A group cannot be moved into itself or one of its sub-groups".to_string(),
+                ));
+            }
+        }
+        let name = groups[moved].name.clone();
+        validate_group_name(&groups, &name, &parent_id, Some(&id))?;
+
+        let old_parent = groups[moved].parent_id.clone();
+        let mut order = children_of(&groups, &parent_id);
+        order.retain(|&i| i != moved);
+        order.insert(index.min(order.len()), moved);
 
-    groups.push(group.clone());
-    storage::save_groups(&groups)?;
+        groups[moved].parent_id = parent_id;
+        for (position, &i) in order.iter().enumerate() {
+            groups[i].sort_order = position as i32;
+        }
+        if old_parent != groups[moved].parent_id {
+            for (position, i) in children_of(&groups, &old_parent).into_iter().enumerate() {
+                groups[i].sort_order = position as i32;
+            }
+        }
+
+        let group = groups[moved].clone();
+        storage::save_groups(&groups)?;
 
-    Ok(group)
+        Ok(group)
+    })
 }
 
 #[tauri::command]
-pub fn remove_group(id: String) -> Result<(), String> {
-    // Move files to ungrouped
-    let mut files = storage::load_files();
-    for file in files.iter_mut() {
-        if file.group_id.as_ref() == Some(&id) {
-            file.group_id = None;
+pub fn rename_group(id: String, new_name: String) -> Result<(), JooDockError> {
+    journal::record("Rename group", || {
+        let mut groups = storage::load_groups()?;
+        let parent_id = groups
+            .iter()
+            .find(|g| g.id == id)
+            .ok_or_else(|| JooDockError::not_found("group", &id))?
+            .parent_id
+            .clone();
+        let new_name = validate_group_name(&groups, &new_name, &parent_id, Some(&id))?;
+
+        if let Some(group) = groups.iter_mut().find(|g| g.id == id) {
+            group.name = new_name;
+        }
+        storage::save_groups(&groups)
+    })
+}
+
+#[tauri::command]
+pub fn set_group_color(id: String, color: Option<String>) -> Result<FileGroup, JooDockError> {
+    let color = color.map(|c| display::validate_color(&c)).transpose()?;
+    journal::record("Color group", || {
+        let mut groups = storage::load_groups()?;
+        let group = groups
+            .iter_mut()
+            .find(|g| g.id == id)
+            .ok_or_else(|| JooDockError::not_found("group", &id))?;
+        group.color = color;
+        let group = group.clone();
+        storage::save_groups(&groups)?;
+
+        Ok(group)
+    })
+}
+
+#[tauri::command]
+pub fn toggle_group(id: String) -> Result<(), JooDockError> {
+    journal::record("Toggle group", || {
+        let mut groups = storage::load_groups()?;
+        let group = groups
+            .iter_mut()
+            .find(|g| g.id == id)
+            .ok_or_else(|| JooDockError::not_found("group", &id))?;
+        group.is_expanded = !group.is_expanded;
+        storage::save_groups(&groups)
+    })
+}
+
+/// Turns a group into a smart group (or back into a regular one with `None`).
+/// A group that still has pinned files cannot become smart.
+#[tauri::command]
+pub fn set_smart_rule(id: String, rule: Option<SmartRule>) -> Result<FileGroup, JooDockError> {
+    journal::record("Change smart rule", || {
+        if let Some(rule) = &rule {
+            rule.validate()?;
+            if storage::load_files()?
+                .iter()
+                .any(|f| f.group_id.as_deref() == Some(id.as_str()) && !f.is_deleted())
+            {
+                return Err(JooDockError::InvalidInput(
+                    "Move the files out of this group before making it a smart group".to_string(),
+                ));
+            }
+        }
+
+        let mut groups = storage::load_groups()?;
+        let group = groups
+            .iter_mut()
+            .find(|g| g.id == id)
+            .ok_or_else(|| JooDockError::not_found("group", &id))?;
+        group.smart = rule;
+        let group = group.clone();
+        storage::save_groups(&groups)?;
+
+        Ok(group)
+    })
+}
+
+/// Contents of a smart group, from the last refresh unless `refresh` is set
+/// or it has never been evaluated.
+#[tauri::command]
+pub async fn get_smart_group_files(
+    id: String,
+    refresh: Option<bool>,
+) -> Result<Vec<FileItem>, JooDockError> {
+    if !refresh.unwrap_or(false) {
+        if let Some(items) = smart::cached(&id) {
+            return Ok(items);
         }
     }
-    storage::save_files(&files)?;
 
-    // Remove group
-    let mut groups = storage::load_groups();
-    groups.retain(|g| g.id != id);
-    storage::save_groups(&groups)
+    let groups = storage::load_groups()?;
+    let group = groups
+        .iter()
+        .find(|g| g.id == id)
+        .ok_or_else(|| JooDockError::not_found("group", &id))?;
+    smart::refresh_group(group)
 }
 
+/// What `open_group` did with the items of a group.
+#[derive(Debug, Default, Serialize)]
+pub struct GroupLaunch {
+    /// Ids of the items that were opened, in the order they were opened
+    pub opened: Vec<String>,
+    /// Ids of missing files and folders that were left out
+    pub skipped: Vec<String>,
+    pub failed: Vec<LaunchFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LaunchFailure {
+    pub id: String,
+    pub name: String,
+    pub error: JooDockError,
+}
+
+/// Opens every item of a group in display order, waiting `delay_ms` between
+/// items. Missing files are skipped, or with `skip_missing: false` nothing is
+/// opened at all. Commands and app links in the group need `confirmed`, as in
+/// `open_file`.
 #[tauri::command]
-pub fn rename_group(id: String, new_name: String) -> Result<(), String> {
-    let mut groups = storage::load_groups();
-    if let Some(group) = groups.iter_mut().find(|g| g.id == id) {
-        group.name = new_name;
+pub async fn open_group(
+    app: tauri::AppHandle,
+    id: String,
+    delay_ms: Option<u64>,
+    skip_missing: Option<bool>,
+    confirmed: Option<bool>,
+) -> Result<GroupLaunch, JooDockError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        launch_group(
+            &app,
+            &id,
+            delay_ms.unwrap_or(0),
+            skip_missing.unwrap_or(true),
+            confirmed,
+        )
+    })
+    .await
+    .map_err(|e| JooDockError::Shell(e.to_string()))?
+}
+
+fn launch_group(
+    app: &tauri::AppHandle,
+    id: &str,
+    delay_ms: u64,
+    skip_missing: bool,
+    confirmed: Option<bool>,
+) -> Result<GroupLaunch, JooDockError> {
+    let groups = storage::load_groups()?;
+    let group = groups
+        .iter()
+        .find(|g| g.id == id)
+        .ok_or_else(|| JooDockError::not_found("group", id))?;
+    let items: Vec<FileItem> = if group.smart.is_some() {
+        match smart::cached(id) {
+            Some(items) => items,
+            None => smart::refresh_group(group)?,
+        }
+    } else {
+        let files = storage::load_files()?;
+        files::ordered_in_group(&files, &Some(id.to_string()))
+            .into_iter()
+            .map(|i| files[i].clone())
+            .collect()
+    };
+
+    let is_missing =
+        |item: &FileItem| item.item_type.is_on_disk() && !Path::new(&item.path).exists();
+    if !skip_missing {
+        if let Some(item) = items.iter().find(|item| is_missing(item)) {
+            return Err(JooDockError::FileMissing(item.path.clone().into()));
+        }
     }
-    storage::save_groups(&groups)
+    let commands = items
+        .iter()
+        .filter(|item| item.item_type == ItemType::Command)
+        .count();
+    let links = items
+        .iter()
+        .filter(|item| item.item_type == ItemType::Url && launch::needs_confirmation(&item.path))
+        .count();
+    let prompt = match (commands, links) {
+        (0, 0) => None,
+        (_, 0) => Some(format!("Run {} command(s) in {}?", commands, group.name)),
+        (0, _) => Some(format!("Open {} app link(s) in {}?", links, group.name)),
+        _ => Some(format!(
+            "Run {} command(s) and open {} app link(s) in {}?",
+            commands, links, group.name
+        )),
+    };
+    if let Some(prompt) = prompt {
+        files::ensure_confirmed(prompt, confirmed)?;
+    }
+
+    let mut launch = GroupLaunch {
+        skipped: items
+            .iter()
+            .filter(|item| is_missing(item))
+            .map(|item| item.id.clone())
+            .collect(),
+        ..Default::default()
+    };
+    let mut opened_at = Vec::new();
+    for (position, item) in items.iter().filter(|item| !is_missing(item)).enumerate() {
+        if position > 0 && delay_ms > 0 {
+            thread::sleep(Duration::from_millis(delay_ms));
+        }
+        match files::launch_item(app, item) {
+            Ok(()) => {
+                launch.opened.push(item.id.clone());
+                opened_at.push((item, chrono::Utc::now().to_rfc3339()));
+            }
+            Err(error) => launch.failed.push(LaunchFailure {
+                id: item.id.clone(),
+                name: item.name.clone(),
+                error,
+            }),
+        }
+    }
+
+    // Reload, as the dock may have been edited while the items were opening
+    let _lock = storage::lock();
+    let mut files = storage::load_files()?;
+    for (item, at) in &opened_at {
+        if let Some(file) = files.iter_mut().find(|f| f.id == item.id) {
+            file.last_accessed_at = Some(at.clone());
+        }
+        storage::record_open(&item.path);
+    }
+    if !opened_at.is_empty() {
+        storage::save_files(&files)?;
+    }
+
+    Ok(launch)
 }
 
+/// Errors unless `group_id` names an existing regular (non-smart) group.
+pub fn ensure_pinnable(group_id: &str) -> Result<(), JooDockError> {
+    let groups = storage::load_groups()?;
+    let group = groups
+        .iter()
+        .find(|g| g.id == group_id)
+        .ok_or_else(|| JooDockError::not_found("group", group_id))?;
+    if group.smart.is_some() {
+        return Err(JooDockError::InvalidInput(format!(
+            "Files cannot be added to the smart group {}",
+            group.name
+        )));
+    }
+    Ok(())
+}
+
+/// Reorders siblings: the listed groups, which must share a parent, come
+/// first in the given order, followed by their unlisted siblings.
 #[tauri::command]
-pub fn toggle_group(id: String) -> Result<(), String> {
-    let mut groups = storage::load_groups();
-    if let Some(group) = groups.iter_mut().find(|g| g.id == id) {
-        group.is_expanded = !group.is_expanded;
+pub fn reorder_groups(ids: Vec<String>) -> Result<Vec<GroupNode>, JooDockError> {
+    journal::record("Reorder groups", || {
+        let mut groups = storage::load_groups()?;
+
+        let mut parent_id = None;
+        for (i, id) in ids.iter().enumerate() {
+            if ids[..i].contains(id) {
+                return Err(JooDockError::InvalidInput(format!(
+                    "Group {} is listed more than once",
+                    id
+                )));
+            }
+            let group = groups
+                .iter()
+                .find(|g| &g.id == id)
+                .ok_or_else(|| JooDockError::not_found("group", id))?;
+            match &parent_id {
+                None => parent_id = Some(group.parent_id.clone()),
+                Some(parent_id) if parent_id != &group.parent_id => {
+                    return Err(JooDockError::InvalidInput(
+                        "Only groups with the same parent can be reordered together".to_string(),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(parent_id) = parent_id {
+            let mut siblings = children_of(&groups, &parent_id);
+            siblings.sort_by_key(|&i| ids.iter().position(|id| id == &groups[i].id).unwrap_or(ids.len()));
+            for (position, i) in siblings.into_iter().enumerate() {
+                groups[i].sort_order = position as i32;
+            }
+            storage::save_groups(&groups)?;
+        }
+
+        Ok(build_tree(&groups, None))
+    })
+}
+
+/// Ids of every group below `id`, at any depth.
+pub fn descendants(groups: &[FileGroup], id: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut pending = vec![id.to_string()];
+    while let Some(current) = pending.pop() {
+        for group in groups
+            .iter()
+            .filter(|g| g.parent_id.as_deref() == Some(current.as_str()))
+        {
+            if !found.contains(&group.id) {
+                found.push(group.id.clone());
+                pending.push(group.id.clone());
+            }
+        }
+    }
+    found
+}
+
+fn build_tree(groups: &[FileGroup], parent_id: Option<&str>) -> Vec<GroupNode> {
+    children_of(groups, &parent_id.map(str::to_string))
+        .into_iter()
+        .map(|i| GroupNode {
+            group: groups[i].clone(),
+            children: build_tree(groups, Some(&groups[i].id)),
+        })
+        .collect()
+}
+
+/// Indexes of the direct children of `parent_id`, in display order.
+fn children_of(groups: &[FileGroup], parent_id: &Option<String>) -> Vec<usize> {
+    let mut children: Vec<usize> = (0..groups.len())
+        .filter(|&i| &groups[i].parent_id == parent_id)
+        .collect();
+    children.sort_by_key(|&i| groups[i].sort_order);
+    children
+}
+
+fn move_group_files(files: &mut [FileItem], from: &str, to: &Option<String>) {
+    let next = files::next_sort_order(files, to);
+    for (offset, i) in files::ordered_in_group(files, &Some(from.to_string()))
+        .into_iter()
+        .enumerate()
+    {
+        files[i].set_group(to.clone());
+        files[i].sort_order = next + offset as i32;
     }
-    storage::save_groups(&groups)
-}
\ No newline at end of file
+}
+
+/// Trims the name and rejects empty names and names already used by a sibling.
+fn validate_group_name(
+    groups: &[FileGroup],
+    name: &str,
+    parent_id: &Option<String>,
+    except_id: Option<&str>,
+) -> Result<String, JooDockError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(JooDockError::InvalidInput(
+            "Group name cannot be empty".to_string(),
+        ));
+    }
+    if groups.iter().any(|g| {
+        Some(g.id.as_str()) != except_id
+            && &g.parent_id == parent_id
+            && g.name.trim().eq_ignore_ascii_case(name)
+    }) {
+        return Err(JooDockError::duplicate("group", name));
+    }
+    Ok(name.to_string())
+}
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use crate::commands::files;
+use crate::error::JooDockError;
+use crate::health::{self, HealthIssue};
+use crate::journal;
+use crate::storage::{self, FileItem, FileKind};
+
+/// Pinned items whose path no longer exists, with relocation candidates.
+#[tauri::command]
+pub async fn check_health() -> Result<Vec<HealthIssue>, JooDockError> {
+    Ok(health::check(&storage::load_files()?))
+}
+
+/// Points a pinned item at a new path, usually one of its relocation candidates.
+#[tauri::command]
+pub fn relink_file(id: String, path: String) -> Result<FileItem, JooDockError> {
+    let new_path = Path::new(&path);
+    if !new_path.exists() {
+        return Err(JooDockError::FileMissing(new_path.to_path_buf()));
+    }
+
+    let _lock = storage::lock();
+    let mut files = storage::load_files()?;
+    // Archived items neither block the path nor can be relinked
+    if files
+        .iter()
+        .any(|f| f.path == path && f.id != id && !f.is_deleted())
+    {
+        return Err(JooDockError::duplicate("file", path));
+    }
+    let file = files
+        .iter_mut()
+        .find(|f| f.id == id && !f.is_deleted())
+        .ok_or_else(|| JooDockError::not_found("file", &id))?;
+
+    file.set_path(path);
+    let file = file.clone();
+    storage::save_files(&files)?;
+
+    Ok(file)
+}
+
+/// Moves missing items to the archive: the given ids, or every missing item
+/// when `ids` is `None`. Items that exist again are left alone. Returns what
+/// was archived; the clean-up can be undone.
+#[tauri::command]
+pub fn cleanup_missing(ids: Option<Vec<String>>) -> Result<Vec<FileItem>, JooDockError> {
+    journal::record("Clean up missing", || {
+        let mut files = storage::load_files()?;
+        let missing: Vec<FileItem> = files
+            .iter()
+            .filter(|f| {
+                ids.as_ref().is_none_or(|ids| ids.contains(&f.id))
+                    && !f.is_deleted()
+                    && f.item_type.is_on_disk()
+                    && FileKind::of(Path::new(&f.path)) == FileKind::Missing
+            })
+            .cloned()
+            .collect();
+
+        if !missing.is_empty() {
+            for file in &missing {
+                files::archive_file_in(&mut files, &file.id);
+            }
+            storage::save_files(&files)?;
+        }
+
+        Ok(missing)
+    })
+}
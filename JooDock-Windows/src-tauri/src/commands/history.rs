@@ -0,0 +1,33 @@
+use serde::Serialize;
+
+use crate::error::JooDockError;
+use crate::journal::{self, Direction, JournalEntry};
+use crate::storage;
+
+/// Labels of the edits that can be undone and redone, newest last.
+#[derive(Debug, Clone, Serialize)]
+pub struct History {
+    pub undo: Vec<String>,
+    pub redo: Vec<String>,
+}
+
+#[tauri::command]
+pub fn get_history() -> Result<History, JooDockError> {
+    let journal = storage::load_journal()?;
+    let labels = |entries: &[JournalEntry]| entries.iter().map(|e| e.label.clone()).collect();
+    Ok(History {
+        undo: labels(&journal.undo),
+        redo: labels(&journal.redo),
+    })
+}
+
+/// Reverts the last dock edit and returns its label, or `None` if there is none.
+#[tauri::command]
+pub fn undo() -> Result<Option<String>, JooDockError> {
+    Ok(journal::step(Direction::Undo)?.map(|entry| entry.label))
+}
+
+#[tauri::command]
+pub fn redo() -> Result<Option<String>, JooDockError> {
+    Ok(journal::step(Direction::Redo)?.map(|entry| entry.label))
+}
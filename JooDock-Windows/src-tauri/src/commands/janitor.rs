@@ -0,0 +1,16 @@
+use crate::error::JooDockError;
+use crate::janitor::{self, CleanupEntry};
+use crate::storage;
+
+/// Clears expired pins and old inbox items now instead of waiting for the
+/// background sweep.
+#[tauri::command]
+pub fn run_janitor() -> Result<Vec<CleanupEntry>, JooDockError> {
+    janitor::sweep()
+}
+
+/// What the janitor cleared, newest last.
+#[tauri::command]
+pub fn get_janitor_log() -> Result<Vec<CleanupEntry>, JooDockError> {
+    storage::load_janitor_log()
+}
@@ -1,5 +1,16 @@
+pub mod archive;
+pub mod config;
+pub mod fileops;
 pub mod files;
 pub mod groups;
+pub mod health;
+pub mod history;
+pub mod janitor;
+pub mod profiles;
+pub mod rules;
 pub mod search;
 pub mod settings;
+pub mod shell;
+pub mod sync;
+pub mod tags;
 pub mod window;
\ No newline at end of file
@@ -0,0 +1,134 @@
+use tauri::{AppHandle, Emitter};
+
+use crate::error::JooDockError;
+use crate::storage::{self, Profile, ProfileList, DEFAULT_PROFILE_ID};
+use crate::tray;
+
+#[tauri::command]
+pub fn get_profiles() -> Result<ProfileList, JooDockError> {
+    storage::load_profiles()
+}
+
+#[tauri::command]
+pub fn create_profile(
+    app: AppHandle,
+    name: String,
+    own_settings: bool,
+) -> Result<Profile, JooDockError> {
+    let _lock = storage::lock();
+    let mut profiles = storage::load_profiles()?;
+    let profile = new_profile(&profiles, name, own_settings)?;
+
+    // Loading the groups of a new profile saves its default groups
+    let dir = storage::profile_dir_for(&profile.id)?;
+    storage::save_files_to(&dir, &[])?;
+    storage::load_groups_from(&dir)?;
+    if own_settings {
+        storage::write_json(&dir.join("settings.json"), &storage::load_settings()?)?;
+    }
+
+    profiles.profiles.push(profile.clone());
+    storage::save_profiles(&profiles)?;
+    tray::refresh_menu(&app)?;
+
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn duplicate_profile(
+    app: AppHandle,
+    id: String,
+    name: String,
+) -> Result<Profile, JooDockError> {
+    let _lock = storage::lock();
+    let mut profiles = storage::load_profiles()?;
+    let source = profiles
+        .profiles
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| JooDockError::not_found("profile", &id))?;
+
+    let profile = new_profile(&profiles, name, source.own_settings)?;
+    storage::copy_profile_data(&source, &profile)?;
+
+    profiles.profiles.push(profile.clone());
+    storage::save_profiles(&profiles)?;
+    tray::refresh_menu(&app)?;
+
+    Ok(profile)
+}
+
+/// Every store edit resolves the active profile as it goes, so the switch waits
+/// for edits in progress (a janitor sweep, a sync) and holds off new ones.
+#[tauri::command]
+pub fn switch_profile(app: AppHandle, id: String) -> Result<Profile, JooDockError> {
+    let _lock = storage::lock();
+    let mut profiles = storage::load_profiles()?;
+    let profile = profiles
+        .profiles
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| JooDockError::not_found("profile", &id))?;
+
+    profiles.active_id = profile.id.clone();
+    storage::save_profiles(&profiles)?;
+    tray::refresh_menu(&app)?;
+    app.emit("profile-changed", &profile)?;
+
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, id: String) -> Result<(), JooDockError> {
+    let _lock = storage::lock();
+    let mut profiles = storage::load_profiles()?;
+    if !profiles.profiles.iter().any(|p| p.id == id) {
+        return Err(JooDockError::not_found("profile", &id));
+    }
+
+    storage::remove_profile_data(&id)?;
+    profiles.profiles.retain(|p| p.id != id);
+
+    let was_active = profiles.active_id == id;
+    if was_active {
+        profiles.active_id = DEFAULT_PROFILE_ID.to_string();
+    }
+    storage::save_profiles(&profiles)?;
+    tray::refresh_menu(&app)?;
+
+    if was_active {
+        if let Some(profile) = profiles.active() {
+            app.emit("profile-changed", profile)?;
+        }
+    }
+    Ok(())
+}
+
+fn new_profile(
+    profiles: &ProfileList,
+    name: String,
+    own_settings: bool,
+) -> Result<Profile, JooDockError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(JooDockError::InvalidInput(
+            "Profile name cannot be empty".to_string(),
+        ));
+    }
+    if profiles
+        .profiles
+        .iter()
+        .any(|p| p.name.eq_ignore_ascii_case(&name))
+    {
+        return Err(JooDockError::duplicate("profile", name));
+    }
+
+    Ok(Profile {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        own_settings,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
@@ -0,0 +1,82 @@
+use crate::commands::{files, groups};
+use crate::error::JooDockError;
+use crate::journal;
+use crate::rules::{self, RoutingRule, RuleMatcher};
+use crate::storage::{self, FileItem};
+
+#[tauri::command]
+pub fn get_rules() -> Result<Vec<RoutingRule>, JooDockError> {
+    storage::load_rules()
+}
+
+#[tauri::command]
+pub fn add_rule(matcher: RuleMatcher, group_id: String) -> Result<RoutingRule, JooDockError> {
+    matcher.validate()?;
+    groups::ensure_pinnable(&group_id)?;
+
+    let rule = RoutingRule {
+        id: uuid::Uuid::new_v4().to_string(),
+        matcher,
+        group_id,
+        enabled: true,
+    };
+
+    let mut rules = storage::load_rules()?;
+    rules.push(rule.clone());
+    storage::save_rules(&rules)?;
+
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn update_rule(rule: RoutingRule) -> Result<RoutingRule, JooDockError> {
+    rule.matcher.validate()?;
+    groups::ensure_pinnable(&rule.group_id)?;
+
+    let mut rules = storage::load_rules()?;
+    let existing = rules
+        .iter_mut()
+        .find(|r| r.id == rule.id)
+        .ok_or_else(|| JooDockError::not_found("rule", &rule.id))?;
+    *existing = rule.clone();
+    storage::save_rules(&rules)?;
+
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn remove_rule(id: String) -> Result<(), JooDockError> {
+    let mut rules = storage::load_rules()?;
+    if !rules.iter().any(|r| r.id == id) {
+        return Err(JooDockError::not_found("rule", id));
+    }
+    rules.retain(|r| r.id != id);
+    storage::save_rules(&rules)
+}
+
+/// Runs the rules over every ungrouped file and returns the files that moved.
+#[tauri::command]
+pub fn apply_rules_to_ungrouped() -> Result<Vec<FileItem>, JooDockError> {
+    journal::record("Apply rules", || {
+        let rules = storage::load_rules()?;
+        let groups = storage::load_groups()?;
+        let mut files = storage::load_files()?;
+
+        let mut moved = Vec::new();
+        for i in files::ordered_in_group(&files, &None) {
+            if let Some(group_id) = rules::route(&files[i].path, &rules, &groups) {
+                let group_id = Some(group_id);
+                files[i].sort_order = files::next_sort_order(&files, &group_id);
+                files[i].set_group(group_id);
+                moved.push(files[i].clone());
+            }
+        }
+
+        if !moved.is_empty() {
+            files::renumber_group(&mut files, &None);
+            storage::save_files(&files)?;
+        }
+
+        Ok(moved)
+    })
+}
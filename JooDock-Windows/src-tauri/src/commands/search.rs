@@ -1,9 +1,31 @@
-use crate::storage::FileItem;
+use crate::error::JooDockError;
+use crate::metadata::{self, FileEntry};
+use crate::storage::{self, FileItem};
+use crate::tags::TagQuery;
 use std::path::Path;
 use walkdir::WalkDir;
 
+const MAX_RESULTS: usize = 20;
+
+/// Searches the user folders by name. A query with `tag:` filters, such as
+/// `tag:urgent report`, searches the pinned items by tag and name or note instead.
 #[tauri::command]
-pub fn search_files(query: String) -> Vec<FileItem> {
+pub fn search_files(query: String) -> Result<Vec<FileEntry>, JooDockError> {
+    let parsed = TagQuery::parse(&query);
+    if parsed.tags.is_empty() {
+        return Ok(metadata::with_metadata(search(&query, MAX_RESULTS)));
+    }
+
+    let files = storage::load_files()?
+        .into_iter()
+        .filter(|f| !f.is_deleted() && parsed.matches(f))
+        .collect();
+    Ok(metadata::with_metadata(files))
+}
+
+/// Finds up to `limit` files and folders under the user directories whose name
+/// contains `query` (case-insensitive).
+pub fn search(query: &str, limit: usize) -> Vec<FileItem> {
     if query.is_empty() {
         return Vec::new();
     }
@@ -31,36 +53,19 @@ pub fn search_files(query: String) -> Vec<FileItem> {
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_default();
 
-            // Skip hidden files and system directories
-            if name.starts_with('.') || name.starts_with('$') {
-                continue;
-            }
-
-            // Skip common system/cache directories
-            let path_str = path.to_string_lossy().to_lowercase();
-            if path_str.contains("\\appdata\\local\\")
-                || path_str.contains("\\appdata\\locallow\\")
-                || path_str.contains("\\windows\\")
-                || path_str.contains("\\program files")
-                || path_str.contains("\\.git\\")
-                || path_str.contains("\\node_modules\\")
-                || path_str.contains("\\__pycache__\\")
-            {
+            if is_excluded(path, &name) {
                 continue;
             }
 
             // Match by filename
             if name.to_lowercase().contains(&query_lower) {
-                results.push(FileItem {
-                    id: uuid::Uuid::new_v4().to_string(),
+                results.push(FileItem::new(
                     name,
-                    path: path.to_string_lossy().to_string(),
-                    group_id: None,
-                    added_at: chrono::Utc::now().to_rfc3339(),
-                    last_accessed_at: None,
-                });
+                    path.to_string_lossy().to_string(),
+                    None,
+                ));
 
-                if results.len() >= 20 {
+                if results.len() >= limit {
                     return results;
                 }
             }
@@ -70,7 +75,25 @@ pub fn search_files(query: String) -> Vec<FileItem> {
     results
 }
 
-fn get_search_paths() -> Vec<String> {
+/// Hidden files and system/cache directories that never show up in results.
+pub fn is_excluded(path: &Path, name: &str) -> bool {
+    // Skip hidden files and system directories
+    if name.starts_with('.') || name.starts_with('$') {
+        return true;
+    }
+
+    // Skip common system/cache directories
+    let path_str = path.to_string_lossy().to_lowercase();
+    path_str.contains("\\appdata\\local\\")
+        || path_str.contains("\\appdata\\locallow\\")
+        || path_str.contains("\\windows\\")
+        || path_str.contains("\\program files")
+        || path_str.contains("\\.git\\")
+        || path_str.contains("\\node_modules\\")
+        || path_str.contains("\\__pycache__\\")
+}
+
+pub fn get_search_paths() -> Vec<String> {
     let mut paths = Vec::new();
 
     if let Some(home) = dirs::home_dir() {
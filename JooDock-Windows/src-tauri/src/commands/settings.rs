@@ -1,11 +1,17 @@
+use crate::error::JooDockError;
 use crate::storage::{self, Settings};
 
 #[tauri::command]
-pub fn get_settings() -> Settings {
+pub fn get_settings() -> Result<Settings, JooDockError> {
     storage::load_settings()
 }
 
 #[tauri::command]
-pub fn save_settings(settings: Settings) -> Result<(), String> {
+pub fn save_settings(settings: Settings) -> Result<(), JooDockError> {
     storage::save_settings(&settings)
-}
\ No newline at end of file
+}
+
+#[tauri::command]
+pub fn get_data_dir() -> Result<String, JooDockError> {
+    storage::get_data_dir().map(|dir| dir.to_string_lossy().to_string())
+}
@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::JooDockError;
+use crate::storage;
+
+/// Opens Explorer on the containing folder with the file selected.
+#[tauri::command]
+pub fn reveal_in_explorer(path: String) -> Result<(), JooDockError> {
+    let path = existing(&path)?;
+
+    #[cfg(windows)]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+        let mut command = Command::new("explorer.exe");
+        // Explorer parses its own command line; the comma form must not be re-quoted
+        command.raw_arg(format!("/select,\"{}\"", path.display()));
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(&path);
+        command
+    };
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let mut command = {
+        let mut command = Command::new("xdg-open");
+        command.arg(path.parent().unwrap_or(&path));
+        command
+    };
+
+    spawn(&mut command, "Explorer")
+}
+
+/// Opens a file with `application` (the path to an executable) and remembers
+/// it for the file's extension. Without an application, Windows' own
+/// "Open with" chooser is shown instead.
+#[tauri::command]
+pub fn open_with(path: String, application: Option<String>) -> Result<(), JooDockError> {
+    let path = existing(&path)?;
+
+    let Some(application) = application.filter(|a| !a.trim().is_empty()) else {
+        return show_open_with_dialog(&path);
+    };
+    let exe = existing(&application)?;
+
+    spawn(Command::new(&exe).arg(&path), &application)?;
+    storage::remember_open_with(&extension_of(&path), &application)
+}
+
+/// Applications previously used to open files with the same extension, most recent first.
+#[tauri::command]
+pub fn get_open_with_apps(path: String) -> Result<Vec<String>, JooDockError> {
+    let extension = extension_of(Path::new(&path));
+    Ok(storage::load_open_with()?
+        .remove(&extension)
+        .unwrap_or_default())
+}
+
+/// Opens a terminal in the folder (or the folder containing the file).
+#[tauri::command]
+pub fn open_terminal(path: String) -> Result<(), JooDockError> {
+    let path = existing(&path)?;
+    let dir = if path.is_dir() {
+        path
+    } else {
+        path.parent().map(Path::to_path_buf).unwrap_or(path)
+    };
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+
+        // Prefer Windows Terminal, fall back to a plain console
+        if Command::new("wt.exe").arg("-d").arg(&dir).spawn().is_ok() {
+            return Ok(());
+        }
+        spawn(
+            Command::new("cmd.exe")
+                .current_dir(&dir)
+                .creation_flags(CREATE_NEW_CONSOLE),
+            "cmd.exe",
+        )
+    }
+    #[cfg(target_os = "macos")]
+    {
+        spawn(Command::new("open").args(["-a", "Terminal"]).arg(&dir), "Terminal")
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        spawn(
+            Command::new("x-terminal-emulator").current_dir(&dir),
+            "x-terminal-emulator",
+        )
+    }
+}
+
+/// Puts the paths on the clipboard as text, one per line.
+#[tauri::command]
+pub fn copy_path(paths: Vec<String>) -> Result<(), JooDockError> {
+    if paths.is_empty() {
+        return Err(JooDockError::InvalidInput("No paths to copy".to_string()));
+    }
+    set_clipboard_text(&paths.join("\r\n"))
+}
+
+/// Puts the files on the clipboard so they can be pasted in Explorer.
+#[tauri::command]
+pub fn copy_file(paths: Vec<String>) -> Result<(), JooDockError> {
+    if paths.is_empty() {
+        return Err(JooDockError::InvalidInput("No files to copy".to_string()));
+    }
+    for path in &paths {
+        existing(path)?;
+    }
+    set_clipboard_files(&paths)
+}
+
+/// Shows the Explorer properties dialog. Runs on the main thread, whose
+/// message loop the dialog needs.
+#[tauri::command]
+pub fn show_properties(path: String) -> Result<(), JooDockError> {
+    let path = existing(&path)?;
+
+    #[cfg(windows)]
+    {
+        use windows::core::{HSTRING, PCWSTR};
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::Shell::{SHObjectProperties, SHOP_FILEPATH};
+
+        let shown = unsafe {
+            SHObjectProperties(
+                HWND::default(),
+                SHOP_FILEPATH,
+                &HSTRING::from(path.as_os_str()),
+                PCWSTR::null(),
+            )
+        };
+        if !shown.as_bool() {
+            return Err(JooDockError::Shell(format!(
+                "Could not show properties for {}",
+                path.display()
+            )));
+        }
+        Ok(())
+    }
+    #[cfg(not(windows))]
+    {
+        Err(JooDockError::Shell(format!(
+            "Properties are only available on Windows ({})",
+            path.display()
+        )))
+    }
+}
+
+fn existing(path: &str) -> Result<PathBuf, JooDockError> {
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        return Err(JooDockError::FileMissing(path));
+    }
+    Ok(path)
+}
+
+/// Lowercase extension without the dot; files without one share the empty key.
+fn extension_of(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn spawn(command: &mut Command, program: &str) -> Result<(), JooDockError> {
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| JooDockError::Shell(format!("Could not start {}: {}", program, e)))
+}
+
+#[cfg(windows)]
+fn show_open_with_dialog(path: &Path) -> Result<(), JooDockError> {
+    use std::os::windows::process::CommandExt;
+    spawn(
+        Command::new("rundll32.exe").raw_arg(format!(
+            "shell32.dll,OpenAs_RunDLL {}",
+            path.display()
+        )),
+        "rundll32.exe",
+    )
+}
+
+#[cfg(not(windows))]
+fn show_open_with_dialog(_path: &Path) -> Result<(), JooDockError> {
+    Err(JooDockError::InvalidInput(
+        "Choose an application to open the file with".to_string(),
+    ))
+}
+
+#[cfg(windows)]
+fn set_clipboard_text(text: &str) -> Result<(), JooDockError> {
+    clipboard_win::set_clipboard_string(text).map_err(|e| JooDockError::Shell(e.to_string()))
+}
+
+#[cfg(windows)]
+fn set_clipboard_files(paths: &[String]) -> Result<(), JooDockError> {
+    let _clipboard = clipboard_win::Clipboard::new_attempts(10)
+        .map_err(|e| JooDockError::Shell(e.to_string()))?;
+    // Clear first so a previously copied text does not stay next to the files
+    clipboard_win::raw::set_file_list_with(paths, clipboard_win::options::DoClear)
+        .map_err(|e| JooDockError::Shell(e.to_string()))
+}
+
+#[cfg(not(windows))]
+fn set_clipboard_text(_text: &str) -> Result<(), JooDockError> {
+    Err(JooDockError::Shell(
+        "The clipboard is only available on Windows".to_string(),
+    ))
+}
+
+#[cfg(not(windows))]
+fn set_clipboard_files(_paths: &[String]) -> Result<(), JooDockError> {
+    Err(JooDockError::Shell(
+        "The clipboard is only available on Windows".to_string(),
+    ))
+}
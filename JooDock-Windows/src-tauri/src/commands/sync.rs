@@ -0,0 +1,26 @@
+use std::path::Path;
+
+use crate::error::JooDockError;
+use crate::storage;
+use crate::sync::{self, SyncReport};
+
+#[tauri::command]
+pub fn set_sync_folder(path: Option<String>) -> Result<(), JooDockError> {
+    let path = path.filter(|p| !p.trim().is_empty());
+    if let Some(path) = &path {
+        if !Path::new(path).is_dir() {
+            return Err(JooDockError::FileMissing(path.into()));
+        }
+    }
+
+    let mut settings = storage::load_settings()?;
+    settings.sync_folder = path;
+    storage::save_settings(&settings)
+}
+
+#[tauri::command]
+pub async fn sync_now() -> Result<SyncReport, JooDockError> {
+    sync::sync_active_profile()?.ok_or_else(|| {
+        JooDockError::InvalidInput("No sync folder is configured".to_string())
+    })
+}
@@ -0,0 +1,87 @@
+use serde::Serialize;
+
+use crate::commands::files;
+use crate::error::JooDockError;
+use crate::journal;
+use crate::storage::{self, FileItem};
+use crate::tags::{self, NOTE_LIMIT};
+
+/// A tag and how many pinned items carry it.
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Adds tags to a pinned item, skipping ones it already has.
+#[tauri::command]
+pub fn tag_file(id: String, tags: Vec<String>) -> Result<FileItem, JooDockError> {
+    let tags = tags
+        .iter()
+        .map(|tag| tags::validate_tag(tag))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    journal::record("Tag file", || {
+        files::update_file(&id, |file| {
+            for tag in tags {
+                if !tags::has_tag(file, &tag) {
+                    file.tags.push(tag);
+                }
+            }
+        })
+    })
+}
+
+#[tauri::command]
+pub fn untag_file(id: String, tags: Vec<String>) -> Result<FileItem, JooDockError> {
+    journal::record("Untag file", || {
+        files::update_file(&id, |file| {
+            file.tags
+                .retain(|t| !tags.iter().any(|tag| tags::same_tag(t, tag)));
+        })
+    })
+}
+
+/// Sets the note on a pinned item; an empty note removes it.
+#[tauri::command]
+pub fn set_note(id: String, note: Option<String>) -> Result<FileItem, JooDockError> {
+    let note = note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|note| note.chars().count() > NOTE_LIMIT)
+    {
+        return Err(JooDockError::InvalidInput(format!(
+            "Notes can be at most {} characters",
+            NOTE_LIMIT
+        )));
+    }
+
+    journal::record("Edit note", || {
+        files::update_file(&id, |file| file.note = note)
+    })
+}
+
+/// Every tag in use with its item count, most used first. Tags that differ
+/// only in case are counted together under their first spelling.
+#[tauri::command]
+pub fn get_tags() -> Result<Vec<TagCount>, JooDockError> {
+    let mut counts: Vec<TagCount> = Vec::new();
+    let files = storage::load_files()?;
+    for tag in files.iter().filter(|f| !f.is_deleted()).flat_map(|f| &f.tags) {
+        match counts.iter_mut().find(|c| tags::same_tag(&c.tag, tag)) {
+            Some(count) => count.count += 1,
+            None => counts.push(TagCount {
+                tag: tag.clone(),
+                count: 1,
+            }),
+        }
+    }
+    counts.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.tag.to_lowercase().cmp(&b.tag.to_lowercase()))
+    });
+    Ok(counts)
+}
@@ -1,29 +1,32 @@
 use std::sync::atomic::Ordering;
 use tauri::Manager;
 
+use crate::error::JooDockError;
+use crate::smart;
 use crate::POPUP_VISIBLE;
 
 #[tauri::command]
-pub fn show_popup(app: tauri::AppHandle) -> Result<(), String> {
+pub fn show_popup(app: tauri::AppHandle) -> Result<(), JooDockError> {
     if let Some(window) = app.get_webview_window("main") {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
+        window.show()?;
+        window.set_focus()?;
         POPUP_VISIBLE.store(true, Ordering::SeqCst);
+        smart::refresh_in_background(&app);
     }
     Ok(())
 }
 
 #[tauri::command]
-pub fn hide_popup(app: tauri::AppHandle) -> Result<(), String> {
+pub fn hide_popup(app: tauri::AppHandle) -> Result<(), JooDockError> {
     if let Some(window) = app.get_webview_window("main") {
-        window.hide().map_err(|e| e.to_string())?;
+        window.hide()?;
         POPUP_VISIBLE.store(false, Ordering::SeqCst);
     }
     Ok(())
 }
 
 #[tauri::command]
-pub fn toggle_popup(app: tauri::AppHandle) -> Result<(), String> {
+pub fn toggle_popup(app: tauri::AppHandle) -> Result<(), JooDockError> {
     let is_visible = POPUP_VISIBLE.load(Ordering::SeqCst);
     if is_visible {
         hide_popup(app)
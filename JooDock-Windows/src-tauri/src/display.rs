@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use crate::error::JooDockError;
+use crate::storage::FileItem;
+
+pub const ALIAS_LIMIT: usize = 100;
+
+/// Checks a `#rgb` or `#rrggbb` color picked by the user and returns it lowercased.
+pub fn validate_color(color: &str) -> Result<String, JooDockError> {
+    let color = color.trim();
+    let valid = color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    });
+    if !valid {
+        return Err(JooDockError::InvalidInput(format!(
+            "{} is not a color like #3b82f6",
+            color
+        )));
+    }
+    Ok(color.to_lowercase())
+}
+
+/// Checks an alias and returns it trimmed; an empty alias means none.
+pub fn validate_alias(alias: Option<String>) -> Result<Option<String>, JooDockError> {
+    let alias = alias
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty());
+    if alias
+        .as_ref()
+        .is_some_and(|alias| alias.chars().count() > ALIAS_LIMIT)
+    {
+        return Err(JooDockError::InvalidInput(format!(
+            "Names can be at most {} characters",
+            ALIAS_LIMIT
+        )));
+    }
+    Ok(alias)
+}
+
+/// For each item, the nearest parent folders that tell it apart from the other
+/// items with the same display name, e.g. `repo-a` for `C:\src\repo-a\README.md`
+/// next to `C:\src\repo-b\README.md`. `None` when the name is unique.
+pub fn parent_hints(files: &[FileItem]) -> Vec<Option<String>> {
+    let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        by_name
+            .entry(file.display_name().to_lowercase())
+            .or_default()
+            .push(i);
+    }
+
+    let mut hints = vec![None; files.len()];
+    for same_name in by_name.values().filter(|indexes| indexes.len() > 1) {
+        let parents: Vec<Vec<&str>> = same_name.iter().map(|&i| parents(&files[i].path)).collect();
+        for (position, &i) in same_name.iter().enumerate() {
+            hints[i] = shortest_unique_suffix(&parents, position);
+        }
+    }
+    hints
+}
+
+/// Folders above the file, nearest first.
+fn parents(path: &str) -> Vec<&str> {
+    let mut components: Vec<&str> = path.split(['/', '\\']).filter(|c| !c.is_empty()).collect();
+    components.pop();
+    components.reverse();
+    components
+}
+
+/// The fewest nearest parents of `parents[own]` that no other entry ends with,
+/// in path order. Falls back to every parent when none is unique.
+fn shortest_unique_suffix(parents: &[Vec<&str>], own: usize) -> Option<String> {
+    let mine = &parents[own];
+    if mine.is_empty() {
+        return None;
+    }
+    let same_suffix = |other: &Vec<&str>, len: usize| {
+        other.len() >= len
+            && other[..len]
+                .iter()
+                .zip(&mine[..len])
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    };
+
+    let len = (1..=mine.len())
+        .find(|&len| {
+            parents
+                .iter()
+                .enumerate()
+                .all(|(i, other)| i == own || !same_suffix(other, len))
+        })
+        .unwrap_or(mine.len());
+    let mut hint: Vec<&str> = mine[..len].to_vec();
+    hint.reverse();
+    Some(hint.join(std::path::MAIN_SEPARATOR_STR))
+}
@@ -0,0 +1,179 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+const DISK_FULL_OS_ERRORS: &[i32] = &[39, 112]; // ERROR_HANDLE_DISK_FULL, ERROR_DISK_FULL
+#[cfg(not(windows))]
+const DISK_FULL_OS_ERRORS: &[i32] = &[28]; // ENOSPC
+
+/// Error returned by storage and every command.
+///
+/// Serialized to the frontend as `{ code, message, details }` so the UI can
+/// pick a localized message from `code` instead of parsing `message`.
+#[derive(Debug)]
+pub enum JooDockError {
+    FileMissing(PathBuf),
+    NotFound { entity: &'static str, id: String },
+    Duplicate { entity: &'static str, value: String },
+    InvalidInput(String),
+    /// The user has to confirm before this runs, e.g. a pinned shell command
+    ConfirmationRequired(String),
+    PermissionDenied(PathBuf),
+    DiskFull(PathBuf),
+    Io { path: PathBuf, source: io::Error },
+    CorruptData { path: PathBuf, source: serde_json::Error },
+    Serialization(serde_json::Error),
+    Shell(String),
+    Window(String),
+}
+
+impl JooDockError {
+    /// Classifies an I/O error on `path` into the most specific variant.
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if source
+            .raw_os_error()
+            .map(|code| DISK_FULL_OS_ERRORS.contains(&code))
+            .unwrap_or(false)
+        {
+            return JooDockError::DiskFull(path);
+        }
+        match source.kind() {
+            io::ErrorKind::NotFound => JooDockError::FileMissing(path),
+            io::ErrorKind::PermissionDenied => JooDockError::PermissionDenied(path),
+            _ => JooDockError::Io { path, source },
+        }
+    }
+
+    pub fn not_found(entity: &'static str, id: impl Into<String>) -> Self {
+        JooDockError::NotFound {
+            entity,
+            id: id.into(),
+        }
+    }
+
+    pub fn duplicate(entity: &'static str, value: impl Into<String>) -> Self {
+        JooDockError::Duplicate {
+            entity,
+            value: value.into(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            JooDockError::FileMissing(_) => "file_missing",
+            JooDockError::NotFound { .. } => "not_found",
+            JooDockError::Duplicate { .. } => "duplicate",
+            JooDockError::InvalidInput(_) => "invalid_input",
+            JooDockError::ConfirmationRequired(_) => "confirmation_required",
+            JooDockError::PermissionDenied(_) => "permission_denied",
+            JooDockError::DiskFull(_) => "disk_full",
+            JooDockError::Io { .. } => "io",
+            JooDockError::CorruptData { .. } => "corrupt_data",
+            JooDockError::Serialization(_) => "serialization",
+            JooDockError::Shell(_) => "shell",
+            JooDockError::Window(_) => "window",
+        }
+    }
+
+    pub fn details(&self) -> serde_json::Value {
+        match self {
+            JooDockError::FileMissing(path)
+            | JooDockError::PermissionDenied(path)
+            | JooDockError::DiskFull(path) => serde_json::json!({ "path": path }),
+            JooDockError::NotFound { entity, id } => {
+                serde_json::json!({ "entity": entity, "id": id })
+            }
+            JooDockError::Duplicate { entity, value } => {
+                serde_json::json!({ "entity": entity, "value": value })
+            }
+            JooDockError::Io { path, source } => {
+                serde_json::json!({ "path": path, "kind": format!("{:?}", source.kind()) })
+            }
+            JooDockError::CorruptData { path, source } => serde_json::json!({
+                "path": path,
+                "line": source.line(),
+                "column": source.column(),
+            }),
+            JooDockError::InvalidInput(_)
+            | JooDockError::ConfirmationRequired(_)
+            | JooDockError::Serialization(_)
+            | JooDockError::Shell(_)
+            | JooDockError::Window(_) => serde_json::Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for JooDockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JooDockError::FileMissing(path) => {
+                write!(f, "File does not exist: {}", path.display())
+            }
+            JooDockError::NotFound { entity, id } => write!(f, "No {} with id {}", entity, id),
+            JooDockError::Duplicate { entity, value } => {
+                write!(f, "{} already exists: {}", capitalize(entity), value)
+            }
+            JooDockError::InvalidInput(message) => write!(f, "{}", message),
+            JooDockError::ConfirmationRequired(message) => write!(f, "{}", message),
+            JooDockError::PermissionDenied(path) => {
+                write!(f, "Permission denied: {}", path.display())
+            }
+            JooDockError::DiskFull(path) => {
+                write!(f, "Not enough disk space to write {}", path.display())
+            }
+            JooDockError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            JooDockError::CorruptData { path, source } => {
+                write!(f, "Could not read {}: {}", path.display(), source)
+            }
+            JooDockError::Serialization(source) => write!(f, "{}", source),
+            JooDockError::Shell(message) => write!(f, "{}", message),
+            JooDockError::Window(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for JooDockError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JooDockError::Io { source, .. } => Some(source),
+            JooDockError::CorruptData { source, .. } | JooDockError::Serialization(source) => {
+                Some(source)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for JooDockError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("JooDockError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<serde_json::Error> for JooDockError {
+    fn from(source: serde_json::Error) -> Self {
+        JooDockError::Serialization(source)
+    }
+}
+
+impl From<tauri::Error> for JooDockError {
+    fn from(source: tauri::Error) -> Self {
+        JooDockError::Window(source.to_string())
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::error::JooDockError;
+use crate::storage::FileItem;
+
+/// Characters Windows does not allow in file names.
+const INVALID_NAME_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// What to do when the destination of a rename, move or copy already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    /// Leave everything as it is and report the conflict
+    #[default]
+    Fail,
+    /// Use a free name such as `report (2).pdf`
+    KeepBoth,
+    /// Send the existing file to the recycle bin first
+    Replace,
+}
+
+/// An on-disk change made from the dock, kept so it can be undone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum FileOperation {
+    Rename { id: String, from: String, to: String },
+    Move { id: String, from: String, to: String },
+    /// `id` is the item pinned for the copy at `path`
+    Duplicate { id: String, path: String },
+    /// The item as it was before its file went to the recycle bin
+    Trash { file: FileItem },
+}
+
+/// Checks a new file name typed by the user and returns it trimmed.
+pub fn validate_name(name: &str) -> Result<String, JooDockError> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(JooDockError::InvalidInput(
+            "File name cannot be empty".to_string(),
+        ));
+    }
+    if let Some(c) = name.chars().find(|c| INVALID_NAME_CHARS.contains(c) || c.is_control()) {
+        return Err(JooDockError::InvalidInput(format!(
+            "File names cannot contain {:?}",
+            c
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Where `source` should end up at `target`, applying `policy` if something is
+/// already there. A target that is `source` itself (e.g. a case-only rename)
+/// is not a conflict.
+pub fn resolve_target(
+    source: &Path,
+    target: PathBuf,
+    policy: ConflictPolicy,
+) -> Result<PathBuf, JooDockError> {
+    if !target.exists() || same_path(source, &target) {
+        return Ok(target);
+    }
+    match policy {
+        ConflictPolicy::Fail => Err(JooDockError::duplicate(
+            "file",
+            target.to_string_lossy(),
+        )),
+        ConflictPolicy::KeepBoth => Ok(free_path(&target, "")),
+        ConflictPolicy::Replace => {
+            trash(&target)?;
+            Ok(target)
+        }
+    }
+}
+
+/// First path like `name (2).ext` (or `name - Copy (2).ext` with a suffix) that does not exist.
+pub fn free_path(path: &Path, suffix: &str) -> PathBuf {
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    let first = parent.join(format!("{}{}{}", stem, suffix, extension));
+    if !first.exists() {
+        return first;
+    }
+    (2..)
+        .map(|n| parent.join(format!("{}{} ({}){}", stem, suffix, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded range")
+}
+
+/// Renames or moves a file or folder, copying across volumes when needed.
+pub fn move_path(from: &Path, to: &Path) -> Result<(), JooDockError> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_path(from, to)?;
+            let removed = if from.is_dir() {
+                fs::remove_dir_all(from)
+            } else {
+                fs::remove_file(from)
+            };
+            removed.map_err(|e| JooDockError::io(from, e))
+        }
+        Err(e) => Err(JooDockError::io(from, e)),
+    }
+}
+
+/// Copies a file, or a folder with everything in it.
+pub fn copy_path(from: &Path, to: &Path) -> Result<(), JooDockError> {
+    if !from.is_dir() {
+        return fs::copy(from, to)
+            .map(|_| ())
+            .map_err(|e| JooDockError::io(from, e));
+    }
+
+    for entry in WalkDir::new(from).follow_links(false) {
+        let entry = entry.map_err(|e| {
+            let path = e.path().unwrap_or(from).to_path_buf();
+            JooDockError::io(&path, e.into())
+        })?;
+        let relative = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        let target = to.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).map_err(|e| JooDockError::io(&target, e))?;
+        } else {
+            fs::copy(entry.path(), &target).map_err(|e| JooDockError::io(entry.path(), e))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn trash(path: &Path) -> Result<(), JooDockError> {
+    trash::delete(path).map_err(|e| {
+        JooDockError::Shell(format!(
+            "Could not move {} to the recycle bin: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Puts the most recently trashed item that came from `path` back.
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+pub fn restore_from_trash(path: &Path) -> Result<(), JooDockError> {
+    let shell_error = |e: trash::Error| JooDockError::Shell(e.to_string());
+    let item = trash::os_limited::list()
+        .map_err(shell_error)?
+        .into_iter()
+        .filter(|item| same_path(&item.original_path(), path))
+        .max_by_key(|item| item.time_deleted)
+        .ok_or_else(|| JooDockError::FileMissing(path.to_path_buf()))?;
+    trash::os_limited::restore_all([item]).map_err(shell_error)
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+pub fn restore_from_trash(path: &Path) -> Result<(), JooDockError> {
+    Err(JooDockError::Shell(format!(
+        "Restore {} from the Trash by hand",
+        path.display()
+    )))
+}
+
+/// Absolute form of an existing path with `..` and symlinks resolved, without
+/// the `\\?\` prefix Windows adds, so it matches what Explorer shows.
+pub fn canonical_path(path: &Path) -> Result<String, JooDockError> {
+    let canonical = fs::canonicalize(path).map_err(|e| JooDockError::io(path, e))?;
+    let canonical = canonical.to_string_lossy();
+    if let Some(share) = canonical.strip_prefix(r"\\?\UNC\") {
+        return Ok(format!(r"\\{}", share));
+    }
+    Ok(canonical
+        .strip_prefix(r"\\?\")
+        .unwrap_or(&canonical)
+        .to_string())
+}
+
+/// What two paths to the same file have in common; Windows ignores case.
+pub fn path_key(path: &str) -> String {
+    if cfg!(windows) {
+        path.to_lowercase()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Windows paths are case-insensitive.
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    a.to_string_lossy().eq_ignore_ascii_case(&b.to_string_lossy())
+}
@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::commands::search;
+use crate::storage::{FileItem, FileKind};
+
+/// How deep below the old parent folder a moved file is looked for.
+const NEARBY_DEPTH: usize = 3;
+const SEARCH_LIMIT: usize = 200;
+
+/// Recorded when a file is pinned so it can be recognized after a move.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub size: u64,
+    /// NTFS file ID, which survives renames and moves within a volume
+    #[serde(rename = "fileId", default)]
+    pub file_id: Option<u64>,
+}
+
+impl Fingerprint {
+    /// Fingerprint of a regular file; folders and missing paths have none.
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+        Some(Fingerprint {
+            size: metadata.len(),
+            file_id: file_id(path),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RelocateMethod {
+    /// Same NTFS file ID as the pinned file
+    FileId,
+    /// Same name below the folder the file used to be in
+    NearOldLocation,
+    /// Same name found by a search of the user folders
+    Search,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Relocation {
+    pub path: String,
+    pub method: RelocateMethod,
+    /// Same size as when the file was pinned. Files edited since then differ,
+    /// so this only ranks candidates and never rules one out.
+    #[serde(rename = "sizeMatches")]
+    pub size_matches: bool,
+}
+
+/// A pinned item whose path no longer exists, with places it may have moved to.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthIssue {
+    pub file: FileItem,
+    pub candidates: Vec<Relocation>,
+}
+
+/// Missing items among `files`, each with relocation candidates (best first).
+pub fn check(files: &[FileItem]) -> Vec<HealthIssue> {
+    files
+        .iter()
+        .filter(|f| {
+            !f.is_deleted()
+                && f.item_type.is_on_disk()
+                && FileKind::of(Path::new(&f.path)) == FileKind::Missing
+        })
+        .map(|file| {
+            let mut file = file.clone();
+            file.kind = FileKind::Missing;
+            HealthIssue {
+                candidates: relocate(&file, files),
+                file,
+            }
+        })
+        .collect()
+}
+
+/// Places a missing file may have moved to, skipping paths that are already
+/// pinned. File ID matches come first, then candidates of the pinned size.
+pub fn relocate(file: &FileItem, pinned: &[FileItem]) -> Vec<Relocation> {
+    let mut candidates: Vec<Relocation> = Vec::new();
+    let mut push = |path: PathBuf, method: RelocateMethod| {
+        let size_matches = file
+            .fingerprint
+            .as_ref()
+            .is_some_and(|fingerprint| same_size(&path, fingerprint));
+        let path = path.to_string_lossy().to_string();
+        if !candidates.iter().any(|c| c.path == path) && !pinned.iter().any(|f| f.path == path) {
+            candidates.push(Relocation {
+                path,
+                method,
+                size_matches,
+            });
+        }
+    };
+
+    let old_path = Path::new(&file.path);
+    let name = match old_path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => return Vec::new(),
+    };
+
+    if let Some(fingerprint) = &file.fingerprint {
+        if let Some(found) = fingerprint
+            .file_id
+            .and_then(|id| path_for_file_id(old_path, id))
+        {
+            // IDs are only unique per volume, so make sure it is plausibly the same file
+            if same_name(&found, &name) || same_size(&found, fingerprint) {
+                push(found, RelocateMethod::FileId);
+            }
+        }
+    }
+
+    if let Some(root) = old_path.parent().and_then(nearest_existing) {
+        for entry in WalkDir::new(&root)
+            .max_depth(NEARBY_DEPTH)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            if same_name(entry.path(), &name) {
+                push(entry.into_path(), RelocateMethod::NearOldLocation);
+            }
+        }
+    }
+
+    for found in search::search(&name, SEARCH_LIMIT) {
+        let path = PathBuf::from(&found.path);
+        if same_name(&path, &name) {
+            push(path, RelocateMethod::Search);
+        }
+    }
+
+    // Stable, so nearby candidates stay ahead of search results of the same rank
+    candidates.sort_by_key(|c| (c.method != RelocateMethod::FileId, !c.size_matches));
+    candidates
+}
+
+fn same_name(path: &Path, name: &str) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy().eq_ignore_ascii_case(name))
+        .unwrap_or(false)
+}
+
+fn same_size(path: &Path, fingerprint: &Fingerprint) -> bool {
+    fs::metadata(path)
+        .map(|m| m.is_file() && m.len() == fingerprint.size)
+        .unwrap_or(false)
+}
+
+fn nearest_existing(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|p| !p.as_os_str().is_empty() && p.is_dir())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(windows)]
+fn file_id(path: &Path) -> Option<u64> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+    };
+
+    let file = fs::File::open(path).ok()?;
+    let mut info = BY_HANDLE_FILE_INFORMATION::default();
+    unsafe { GetFileInformationByHandle(HANDLE(file.as_raw_handle()), &mut info) }.ok()?;
+    Some((u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow))
+}
+
+#[cfg(not(windows))]
+fn file_id(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Current path of the file with `id` on the volume `old_path` was on.
+#[cfg(windows)]
+fn path_for_file_id(old_path: &Path, id: u64) -> Option<PathBuf> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Storage::FileSystem::{
+        FileIdType, GetFinalPathNameByHandleW, OpenFileById, FILE_FLAG_BACKUP_SEMANTICS,
+        FILE_ID_DESCRIPTOR, FILE_ID_DESCRIPTOR_0, FILE_NAME_NORMALIZED, FILE_SHARE_DELETE,
+        FILE_SHARE_READ, FILE_SHARE_WRITE,
+    };
+
+    // Any open handle on the volume works as the hint
+    let volume_hint = std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS.0)
+        .open(nearest_existing(old_path)?)
+        .ok()?;
+
+    let descriptor = FILE_ID_DESCRIPTOR {
+        dwSize: std::mem::size_of::<FILE_ID_DESCRIPTOR>() as u32,
+        Type: FileIdType,
+        Anonymous: FILE_ID_DESCRIPTOR_0 { FileId: id as i64 },
+    };
+
+    unsafe {
+        let handle = OpenFileById(
+            HANDLE(volume_hint.as_raw_handle()),
+            &descriptor,
+            0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            FILE_FLAG_BACKUP_SEMANTICS,
+        )
+        .ok()?;
+
+        let mut buffer = vec![0u16; 1024];
+        let len = GetFinalPathNameByHandleW(handle, &mut buffer, FILE_NAME_NORMALIZED) as usize;
+        let _ = CloseHandle(handle);
+        if len == 0 || len > buffer.len() {
+            return None;
+        }
+
+        let path = String::from_utf16_lossy(&buffer[..len]);
+        Some(PathBuf::from(path.trim_start_matches(r"\\?\")))
+    }
+}
+
+#[cfg(not(windows))]
+fn path_for_file_id(_old_path: &Path, _id: u64) -> Option<PathBuf> {
+    None
+}
@@ -9,6 +9,7 @@ use windows::Win32::Foundation::POINT;
 #[cfg(windows)]
 use windows::Win32::Graphics::Gdi::{GetSystemMetrics, SM_CXSCREEN};
 
+use crate::smart;
 use crate::POPUP_VISIBLE;
 
 const HOVER_ZONE_WIDTH: i32 = 300;
@@ -64,6 +65,7 @@ pub fn start_monitoring(app: AppHandle) {
                             let _ = window.show();
                             let _ = window.set_focus();
                             POPUP_VISIBLE.store(true, Ordering::SeqCst);
+                            smart::refresh_in_background(&app);
                         }
                         hover_start = None;
                     }
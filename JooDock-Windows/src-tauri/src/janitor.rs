@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::files;
+use crate::error::JooDockError;
+use crate::storage::{self, FileItem, InboxAction, InboxSettings};
+
+const SWEEP_INTERVAL_SECS: u64 = 10 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CleanupReason {
+    /// The item's `expiresAt` passed
+    Expired,
+    /// The item sat in the inbox group for longer than `maxAgeDays`
+    Inbox,
+    /// The item was in the archive for longer than `archiveRetentionDays`
+    Retention,
+}
+
+/// One item the janitor cleared, as kept in its log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupEntry {
+    pub at: String,
+    pub reason: CleanupReason,
+    pub action: InboxAction,
+    /// The item as it was before it was cleared
+    pub file: FileItem,
+}
+
+/// Archives or removes expired pins and old inbox items, purges archived
+/// items past the retention period, logs all of it and returns what was
+/// cleared. Sweeps stay out of the undo journal, so they never take the place
+/// of the user's own edits; archived items can be restored from the archive.
+pub fn sweep() -> Result<Vec<CleanupEntry>, JooDockError> {
+    // Hold the store from the scan to the log, so no edit lands in between
+    let _lock = storage::lock();
+    let settings = storage::load_settings()?;
+    let inbox = settings.inbox;
+    let now = Utc::now();
+    let mut files = storage::load_files()?;
+    let due: Vec<(String, CleanupReason)> = files
+        .iter()
+        .filter_map(|f| due_reason(f, &inbox, now).map(|reason| (f.id.clone(), reason)))
+        .chain(
+            files
+                .iter()
+                .filter(|f| past_retention(f, settings.archive_retention_days, now))
+                .map(|f| (f.id.clone(), CleanupReason::Retention)),
+        )
+        .collect();
+    if due.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for (id, reason) in due {
+        let Some(file) = files.iter().find(|f| f.id == id).cloned() else {
+            continue;
+        };
+        let action = match reason {
+            CleanupReason::Retention => InboxAction::Remove,
+            CleanupReason::Expired | CleanupReason::Inbox => inbox.action,
+        };
+        match action {
+            InboxAction::Archive => {
+                files::archive_file_in(&mut files, &id);
+            }
+            InboxAction::Remove => {
+                files.retain(|f| f.id != id);
+                files::renumber_group(&mut files, &file.group_id);
+            }
+        }
+        entries.push(CleanupEntry {
+            at: now.to_rfc3339(),
+            reason,
+            action,
+            file,
+        });
+    }
+    storage::save_files(&files)?;
+
+    let mut log = storage::load_janitor_log()?;
+    log.extend(entries.iter().cloned());
+    storage::save_janitor_log(&log)?;
+
+    Ok(entries)
+}
+
+/// Sweeps at startup and then periodically, telling the UI when it cleared something.
+pub fn start_janitor(app: AppHandle) {
+    loop {
+        if let Ok(entries) = sweep() {
+            if !entries.is_empty() {
+                let _ = app.emit("janitor-swept", &entries);
+            }
+        }
+        std::thread::sleep(Duration::from_secs(SWEEP_INTERVAL_SECS));
+    }
+}
+
+fn due_reason(file: &FileItem, inbox: &InboxSettings, now: DateTime<Utc>) -> Option<CleanupReason> {
+    if file.is_deleted() {
+        return None;
+    }
+    let passed = |at: &str| DateTime::parse_from_rfc3339(at).is_ok_and(|at| at <= now);
+    if file.expires_at.as_deref().is_some_and(passed) {
+        return Some(CleanupReason::Expired);
+    }
+
+    // Age counts from when the item arrived in the inbox
+    let in_inbox = inbox.group_id.is_some() && file.group_id == inbox.group_id;
+    let max_age = chrono::Duration::days(inbox.max_age_days.into());
+    let arrived_at = file.moved_at.as_deref().unwrap_or(&file.added_at);
+    // An age past the end of the calendar never comes
+    let too_old = DateTime::parse_from_rfc3339(arrived_at).is_ok_and(|arrived_at| {
+        arrived_at
+            .checked_add_signed(max_age)
+            .is_some_and(|due| due <= now)
+    });
+    (in_inbox && too_old).then_some(CleanupReason::Inbox)
+}
+
+/// Whether an archived item has been there for longer than `retention_days`.
+fn past_retention(file: &FileItem, retention_days: u32, now: DateTime<Utc>) -> bool {
+    let retention = chrono::Duration::days(retention_days.into());
+    retention_days > 0
+        && file.deleted_at.as_deref().is_some_and(|at| {
+            DateTime::parse_from_rfc3339(at).is_ok_and(|at| {
+                at.checked_add_signed(retention)
+                    .is_some_and(|due| due <= now)
+            })
+        })
+}
@@ -0,0 +1,212 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::changes::{Change, Dock};
+use crate::error::JooDockError;
+use crate::storage;
+
+/// One undoable dock edit, stored as the changes that revert and re-apply it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// What the user did, e.g. "Remove group"
+    pub label: String,
+    pub at: String,
+    pub undo: Vec<Change>,
+    pub redo: Vec<Change>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    pub undo: Vec<JournalEntry>,
+    pub redo: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Undo,
+    Redo,
+}
+
+/// Runs a dock edit and journals how to revert it. Edits that fail or change
+/// nothing are not recorded; a recorded edit clears the redo history.
+pub fn record<T>(
+    label: &str,
+    edit: impl FnOnce() -> Result<T, JooDockError>,
+) -> Result<T, JooDockError> {
+    let _lock = storage::lock();
+    let before = current_dock()?;
+    let result = edit()?;
+    let after = current_dock()?;
+
+    let redo = before.diff(&after);
+    if !redo.is_empty() {
+        let mut journal = storage::load_journal()?;
+        journal.undo.push(JournalEntry {
+            label: label.to_string(),
+            at: chrono::Utc::now().to_rfc3339(),
+            undo: after.diff(&before),
+            redo,
+        });
+        journal.redo.clear();
+        storage::save_journal(&journal)?;
+    }
+
+    Ok(result)
+}
+
+/// Reverts (or re-applies) the newest entry and moves it to the other stack.
+/// Returns `None` when there is nothing to undo (or redo). Only the fields the
+/// entry changed are put back, and only where they still hold what the entry
+/// left behind, so edits that are not journaled (a rename on disk, a relink,
+/// a synced edit) survive.
+pub fn step(direction: Direction) -> Result<Option<JournalEntry>, JooDockError> {
+    let _lock = storage::lock();
+    let mut journal = storage::load_journal()?;
+    let (from, to) = match direction {
+        Direction::Undo => (&mut journal.undo, &mut journal.redo),
+        Direction::Redo => (&mut journal.redo, &mut journal.undo),
+    };
+    let Some(entry) = from.pop() else {
+        return Ok(None);
+    };
+
+    let (changes, left_behind) = match direction {
+        Direction::Undo => (&entry.undo, &entry.redo),
+        Direction::Redo => (&entry.redo, &entry.undo),
+    };
+    let mut dock = current_dock()?;
+    for change in changes {
+        if let Some(change) = rebase(change, left_behind, &dock)? {
+            dock.apply(change);
+        }
+    }
+    // Later edits may have removed a group the entry refers to
+    dock.detach_orphans();
+    storage::save_groups(&dock.groups)?;
+    storage::save_files(&dock.files)?;
+
+    to.push(entry.clone());
+    storage::save_journal(&journal)?;
+
+    Ok(Some(entry))
+}
+
+/// `change` adjusted to the current dock, or `None` if later edits already
+/// removed (or brought back) its record. `left_behind` holds the records as
+/// the entry left them.
+fn rebase(
+    change: &Change,
+    left_behind: &[Change],
+    dock: &Dock,
+) -> Result<Option<Change>, JooDockError> {
+    match change {
+        Change::PutFile { file } => {
+            let left = left_behind.iter().find_map(|c| match c {
+                Change::PutFile { file: left } if left.id == file.id => Some(left),
+                _ => None,
+            });
+            let current = dock.files.iter().find(|f| f.id == file.id);
+            Ok(rebase_record(file, left, current)?.map(|file| Change::PutFile { file }))
+        }
+        Change::PutGroup { group } => {
+            let left = left_behind.iter().find_map(|c| match c {
+                Change::PutGroup { group: left } if left.id == group.id => Some(left),
+                _ => None,
+            });
+            let current = dock.groups.iter().find(|g| g.id == group.id);
+            Ok(rebase_record(group, left, current)?.map(|group| Change::PutGroup { group }))
+        }
+        Change::DeleteFile { .. } | Change::DeleteGroup { .. } => Ok(Some(change.clone())),
+    }
+}
+
+fn rebase_record<T: Clone + Serialize + DeserializeOwned>(
+    target: &T,
+    left: Option<&T>,
+    current: Option<&T>,
+) -> Result<Option<T>, JooDockError> {
+    match (left, current) {
+        (Some(left), Some(current)) => merge_fields(target, left, current).map(Some),
+        // Removed since the entry was made
+        (Some(_), None) => Ok(None),
+        // Brought back since the entry removed it
+        (None, Some(_)) => Ok(None),
+        (None, None) => Ok(Some(target.clone())),
+    }
+}
+
+/// `current` with each field that still equals `left` set to `target`'s value.
+fn merge_fields<T: Serialize + DeserializeOwned>(
+    target: &T,
+    left: &T,
+    current: &T,
+) -> Result<T, JooDockError> {
+    let (Value::Object(target), Value::Object(left), Value::Object(mut merged)) = (
+        serde_json::to_value(target)?,
+        serde_json::to_value(left)?,
+        serde_json::to_value(current)?,
+    ) else {
+        return Ok(serde_json::from_value(serde_json::to_value(current)?)?);
+    };
+    let keys: Vec<String> = target.keys().chain(left.keys()).cloned().collect();
+    for key in keys {
+        if merged.get(&key) == left.get(&key) {
+            match target.get(&key) {
+                Some(value) => merged.insert(key, value.clone()),
+                None => merged.remove(&key),
+            };
+        }
+    }
+    Ok(serde_json::from_value(Value::Object(merged))?)
+}
+
+fn current_dock() -> Result<Dock, JooDockError> {
+    Ok(Dock {
+        files: storage::load_files()?,
+        groups: storage::load_groups()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileItem;
+
+    #[test]
+    fn undo_keeps_later_edits_to_other_fields() {
+        let before = FileItem::new("a.pdf".to_string(), "C:/Docs/a.pdf".to_string(), None);
+        let mut after = before.clone();
+        after.alias = Some("Invoice".to_string());
+        let entry_undo = [Change::PutFile {
+            file: before.clone(),
+        }];
+        let entry_redo = [Change::PutFile {
+            file: after.clone(),
+        }];
+
+        // Renamed on disk after the alias was set
+        let mut current = after.clone();
+        current.set_path("C:/Docs/b.pdf".to_string());
+        let dock = Dock {
+            files: vec![current.clone()],
+            groups: Vec::new(),
+        };
+
+        let undone = rebase(&entry_undo[0], &entry_redo, &dock).unwrap();
+        let Some(Change::PutFile { file }) = undone else {
+            panic!("expected a file change");
+        };
+        assert_eq!(file.alias, None);
+        assert_eq!(file.path, "C:/Docs/b.pdf");
+        assert_eq!(file.name, "b.pdf");
+    }
+
+    #[test]
+    fn undo_skips_records_removed_since() {
+        let file = FileItem::new("a.pdf".to_string(), "C:/a.pdf".to_string(), None);
+        let undo = Change::PutFile { file: file.clone() };
+        let redo = [Change::PutFile { file }];
+        assert_eq!(rebase(&undo, &redo, &Dock::default()).unwrap(), None);
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::JooDockError;
+
+/// Schemes that would run script or read local files through the browser.
+const BLOCKED_SCHEMES: &[&str] = &["javascript", "vbscript", "data", "file"];
+/// Schemes opened without asking. Any other scheme starts whichever app
+/// registered it (`ms-msdt:`, `search-ms:`...), so it is confirmed like a command.
+const TRUSTED_SCHEMES: &[&str] = &["http", "https", "mailto", "tel"];
+
+/// How a `Command` item is run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Launch {
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Defaults to the folder the program is in
+    #[serde(rename = "workingDir", default)]
+    pub working_dir: Option<String>,
+}
+
+/// Checks a link typed or dropped by the user and returns it trimmed. Any
+/// scheme works (`https:`, `mailto:`, `slack:`...) except the blocked ones;
+/// see `needs_confirmation` for opening them.
+pub fn validate_url(url: &str) -> Result<String, JooDockError> {
+    let url = url.trim();
+    let parsed = url::Url::parse(url)
+        .map_err(|e| JooDockError::InvalidInput(format!("{} is not a valid link: {}", url, e)))?;
+    let scheme = parsed.scheme();
+    // `C:\Docs\a.pdf` parses as a link with the drive letter as its scheme
+    if scheme.len() < 2 || BLOCKED_SCHEMES.contains(&scheme) {
+        return Err(JooDockError::InvalidInput(format!(
+            "{}: links cannot be pinned",
+            scheme
+        )));
+    }
+    Ok(url.to_string())
+}
+
+/// Whether opening `url` needs the user's confirmation. Paths are not links,
+/// including `C:\Docs\a.pdf` with its one-letter "scheme".
+pub fn needs_confirmation(url: &str) -> bool {
+    url::Url::parse(url.trim()).is_ok_and(|parsed| {
+        let scheme = parsed.scheme();
+        scheme.len() > 1 && !TRUSTED_SCHEMES.contains(&scheme)
+    })
+}
+
+/// Display name for a link: its host, or the address of a `mailto:` link.
+pub fn url_name(url: &str) -> String {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+    match parsed.host_str() {
+        Some(host) => host.trim_start_matches("www.").to_string(),
+        None if !parsed.path().is_empty() => parsed.path().to_string(),
+        None => url.to_string(),
+    }
+}
+
+/// Checks a command before it is pinned. A bare program name such as
+/// `python` is looked up on `PATH` when it runs, so only paths must exist.
+pub fn validate_command(program: &str, launch: &Launch) -> Result<(), JooDockError> {
+    if program.trim().is_empty() {
+        return Err(JooDockError::InvalidInput(
+            "Command cannot be empty".to_string(),
+        ));
+    }
+    let path = Path::new(program);
+    if path.components().count() > 1 && !path.exists() {
+        return Err(JooDockError::FileMissing(path.to_path_buf()));
+    }
+    if let Some(dir) = &launch.working_dir {
+        if !Path::new(dir).is_dir() {
+            return Err(JooDockError::FileMissing(PathBuf::from(dir)));
+        }
+    }
+    Ok(())
+}
+
+/// Starts `program` without waiting for it to finish.
+pub fn run(program: &str, launch: &Launch) -> Result<(), JooDockError> {
+    let mut command = Command::new(program);
+    command.args(&launch.args);
+    let working_dir = match &launch.working_dir {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => Path::new(program)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf),
+    };
+    if let Some(dir) = working_dir {
+        command.current_dir(dir);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| JooDockError::Shell(format!("Could not run {}: {}", program, e)))?;
+    // Reap the process when it exits so it does not linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -1,9 +1,26 @@
+mod bundle;
+mod changes;
 mod commands;
+mod display;
+mod error;
+mod fileops;
+mod health;
 mod hotzone;
+mod janitor;
+mod journal;
+mod launch;
+mod metadata;
+mod preview;
+mod rules;
+mod smart;
 mod storage;
+mod sync;
+mod tags;
+mod thumbnail;
 mod tray;
 
 use tauri::{Manager, WindowEvent};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -11,10 +28,25 @@ pub static POPUP_VISIBLE: AtomicBool = AtomicBool::new(false);
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    storage::configure_data_dir(std::env::args().skip(1));
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .register_asynchronous_uri_scheme_protocol(thumbnail::SCHEME, |_ctx, request, responder| {
+            // Rendering can be slow, so keep it off the webview's thread
+            thumbnail::respond_later(request, responder);
+        })
         .setup(|app| {
+            // Report an unusable data directory up front; commands keep returning the error
+            if let Err(e) = storage::get_data_dir() {
+                app.dialog()
+                    .message(e.to_string())
+                    .title("JooDock")
+                    .kind(MessageDialogKind::Error)
+                    .show(|_| {});
+            }
+
             let window = app.get_webview_window("main").unwrap();
 
             // Position window at top center
@@ -38,6 +70,24 @@ pub fn run() {
                 hotzone::start_monitoring(app_handle);
             });
 
+            // Start background sync (no-op until a sync folder is set)
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                sync::start_background_sync(app_handle);
+            });
+
+            // Clear expired pins and old inbox items
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                janitor::start_janitor(app_handle);
+            });
+
+            // Re-evaluate smart groups when the folders they read change
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                smart::start_watcher(app_handle);
+            });
+
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -53,17 +103,75 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::files::get_files,
             commands::files::add_file,
+            commands::files::add_files,
+            commands::files::add_url,
+            commands::files::add_command,
+            commands::files::set_alias,
+            commands::files::set_file_color,
+            commands::files::set_expiry,
             commands::files::remove_file,
+            commands::files::move_file,
+            commands::files::list_directory,
+            commands::files::preview_file,
             commands::files::open_file,
             commands::files::get_recent_files,
+            commands::fileops::rename_file_on_disk,
+            commands::fileops::move_file_on_disk,
+            commands::fileops::duplicate_file_on_disk,
+            commands::fileops::trash_file,
+            commands::fileops::get_file_operations,
+            commands::fileops::undo_file_operation,
+            commands::shell::reveal_in_explorer,
+            commands::shell::open_with,
+            commands::shell::get_open_with_apps,
+            commands::shell::open_terminal,
+            commands::shell::copy_path,
+            commands::shell::copy_file,
+            commands::shell::show_properties,
             commands::groups::get_groups,
             commands::groups::add_group,
             commands::groups::remove_group,
             commands::groups::rename_group,
             commands::groups::toggle_group,
+            commands::groups::open_group,
+            commands::groups::set_group_color,
+            commands::groups::move_group,
+            commands::groups::reorder_groups,
+            commands::groups::set_smart_rule,
+            commands::groups::get_smart_group_files,
+            commands::health::check_health,
+            commands::health::relink_file,
+            commands::health::cleanup_missing,
+            commands::history::get_history,
+            commands::history::undo,
+            commands::history::redo,
+            commands::janitor::run_janitor,
+            commands::janitor::get_janitor_log,
+            commands::archive::get_archive,
+            commands::archive::restore_file,
+            commands::archive::purge_archive,
+            commands::rules::get_rules,
+            commands::rules::add_rule,
+            commands::rules::update_rule,
+            commands::rules::remove_rule,
+            commands::rules::apply_rules_to_ungrouped,
             commands::search::search_files,
+            commands::tags::tag_file,
+            commands::tags::untag_file,
+            commands::tags::set_note,
+            commands::tags::get_tags,
             commands::settings::get_settings,
             commands::settings::save_settings,
+            commands::settings::get_data_dir,
+            commands::config::export_config,
+            commands::config::import_config,
+            commands::profiles::get_profiles,
+            commands::profiles::create_profile,
+            commands::profiles::duplicate_profile,
+            commands::profiles::switch_profile,
+            commands::profiles::delete_profile,
+            commands::sync::set_sync_folder,
+            commands::sync::sync_now,
             commands::window::show_popup,
             commands::window::hide_popup,
             commands::window::toggle_popup,
@@ -0,0 +1,150 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::display;
+use crate::storage::FileItem;
+
+/// Entries kept before the cache is dropped and rebuilt.
+const CACHE_LIMIT: usize = 4096;
+
+static CACHE: OnceLock<Mutex<HashMap<String, Cached>>> = OnceLock::new();
+
+/// What the popup shows next to a file without further IPC.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileMetadata {
+    /// In bytes; `None` for folders
+    pub size: Option<u64>,
+    #[serde(rename = "modifiedAt")]
+    pub modified_at: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<String>,
+    /// Lowercase, without the dot
+    pub extension: Option<String>,
+    /// From the file's first bytes, falling back to its extension
+    pub mime: Option<String>,
+    #[serde(rename = "readOnly")]
+    pub read_only: bool,
+    pub hidden: bool,
+}
+
+/// A file as returned to the UI: the stored item plus what is on disk now.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileEntry {
+    #[serde(flatten)]
+    pub file: FileItem,
+    /// `None` when the path cannot be read
+    pub metadata: Option<FileMetadata>,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    /// Parent folders to show next to a display name other items share
+    #[serde(rename = "parentHint")]
+    pub parent_hint: Option<String>,
+}
+
+struct Cached {
+    modified: Option<SystemTime>,
+    len: u64,
+    metadata: FileMetadata,
+}
+
+/// Attaches metadata and display names to every file. Only a `stat` is needed
+/// for files whose size and modification time are unchanged since last seen.
+pub fn with_metadata(files: Vec<FileItem>) -> Vec<FileEntry> {
+    let mut cache = match cache().lock() {
+        Ok(cache) => cache,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if cache.len() > CACHE_LIMIT {
+        cache.clear();
+    }
+
+    let hints = display::parent_hints(&files);
+    files
+        .into_iter()
+        .zip(hints)
+        .map(|(file, parent_hint)| FileEntry {
+            metadata: lookup(&mut cache, &file.path),
+            display_name: file.display_name().to_string(),
+            parent_hint,
+            file,
+        })
+        .collect()
+}
+
+fn lookup(cache: &mut HashMap<String, Cached>, path: &str) -> Option<FileMetadata> {
+    let Ok(stat) = fs::metadata(path) else {
+        cache.remove(path);
+        return None;
+    };
+    let modified = stat.modified().ok();
+
+    if let Some(cached) = cache.get(path) {
+        if cached.modified == modified && cached.len == stat.len() {
+            return Some(cached.metadata.clone());
+        }
+    }
+
+    let metadata = read(Path::new(path), &stat);
+    cache.insert(
+        path.to_string(),
+        Cached {
+            modified,
+            len: stat.len(),
+            metadata: metadata.clone(),
+        },
+    );
+    Some(metadata)
+}
+
+fn read(path: &Path, stat: &fs::Metadata) -> FileMetadata {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .filter(|e| !e.is_empty());
+
+    let mime = if stat.is_file() {
+        infer::get_from_path(path)
+            .ok()
+            .flatten()
+            .map(|kind| kind.mime_type().to_string())
+            .or_else(|| mime_guess::from_path(path).first().map(|m| m.to_string()))
+    } else {
+        None
+    };
+
+    FileMetadata {
+        size: stat.is_file().then(|| stat.len()),
+        modified_at: stat.modified().ok().map(to_rfc3339),
+        created_at: stat.created().ok().map(to_rfc3339),
+        extension,
+        mime,
+        read_only: stat.permissions().readonly(),
+        hidden: is_hidden(path, stat),
+    }
+}
+
+fn to_rfc3339(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()
+}
+
+#[cfg(windows)]
+fn is_hidden(_path: &Path, stat: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    stat.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0
+}
+
+#[cfg(not(windows))]
+fn is_hidden(path: &Path, _stat: &fs::Metadata) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy().starts_with('.'))
+        .unwrap_or(false)
+}
+
+fn cache() -> &'static Mutex<HashMap<String, Cached>> {
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
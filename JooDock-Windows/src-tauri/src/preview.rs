@@ -0,0 +1,282 @@
+use base64::Engine;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use crate::error::JooDockError;
+use crate::thumbnail;
+
+const MAX_LINES: usize = 40;
+const MAX_LINE_CHARS: usize = 400;
+/// Bytes read when looking for text lines
+const TEXT_READ_LIMIT: u64 = 64 * 1024;
+/// Bytes scanned for a PDF's page objects
+const PDF_READ_LIMIT: u64 = 4 * 1024 * 1024;
+/// Bytes of a tar stream read (after decompression) while listing entries;
+/// the bodies between headers count too
+const TAR_READ_LIMIT: u64 = 16 * 1024 * 1024;
+const MAX_ARCHIVE_ENTRIES: usize = 200;
+const IMAGE_PREVIEW_SIZE: u32 = 256;
+
+/// A bounded look inside a file, for the hover preview in the popup.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Preview {
+    Text {
+        lines: Vec<String>,
+        /// Syntax highlighting language, e.g. `rust` or `markdown`
+        language: Option<String>,
+        truncated: bool,
+    },
+    Image {
+        width: u32,
+        height: u32,
+        /// `data:image/png;base64,...` URL of a scaled-down copy
+        thumbnail: Option<String>,
+    },
+    Archive {
+        format: String,
+        entries: Vec<ArchiveEntry>,
+        truncated: bool,
+    },
+    Pdf {
+        pages: Option<usize>,
+        /// Only the start of the file was scanned, so `pages` is a lower bound
+        truncated: bool,
+    },
+    Directory {
+        entries: Vec<String>,
+        truncated: bool,
+    },
+    /// Nothing to preview, e.g. a binary file
+    None,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+    #[serde(rename = "isDir")]
+    pub is_dir: bool,
+}
+
+pub fn preview(path: &Path) -> Result<Preview, JooDockError> {
+    let stat = fs::metadata(path).map_err(|e| JooDockError::io(path, e))?;
+    if stat.is_dir() {
+        return directory_preview(path);
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if name.ends_with(".tar.gz") || extension == "tgz" {
+        return tar_preview(path, true);
+    }
+    match extension.as_str() {
+        "zip" | "jar" | "docx" | "xlsx" | "pptx" | "odt" | "epub" => return zip_preview(path),
+        "tar" => return tar_preview(path, false),
+        "pdf" => return pdf_preview(path, stat.len()),
+        _ => {}
+    }
+
+    if image::ImageFormat::from_path(path).is_ok() {
+        if let Ok((width, height)) = image::image_dimensions(path) {
+            let thumbnail = thumbnail::thumbnail(path, IMAGE_PREVIEW_SIZE).ok().map(|png| {
+                format!(
+                    "data:image/png;base64,{}",
+                    base64::engine::general_purpose::STANDARD.encode(png)
+                )
+            });
+            return Ok(Preview::Image {
+                width,
+                height,
+                thumbnail,
+            });
+        }
+    }
+
+    text_preview(path, &extension, stat.len())
+}
+
+fn text_preview(path: &Path, extension: &str, len: u64) -> Result<Preview, JooDockError> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|f| f.take(TEXT_READ_LIMIT).read_to_end(&mut bytes))
+        .map_err(|e| JooDockError::io(path, e))?;
+
+    // NUL bytes mean binary (UTF-16 text is rare enough to skip)
+    if bytes.iter().take(8192).any(|&b| b == 0) {
+        return Ok(Preview::None);
+    }
+
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines: Vec<String> = text
+        .lines()
+        .take(MAX_LINES + 1)
+        .map(|line| line.chars().take(MAX_LINE_CHARS).collect())
+        .collect();
+    let truncated = lines.len() > MAX_LINES || len > TEXT_READ_LIMIT;
+    lines.truncate(MAX_LINES);
+
+    let language = language_for(extension)
+        .or_else(|| lines.first().and_then(|line| language_for_shebang(line)))
+        .map(str::to_string);
+
+    Ok(Preview::Text {
+        lines,
+        language,
+        truncated,
+    })
+}
+
+fn language_for(extension: &str) -> Option<&'static str> {
+    let language = match extension {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "py" | "pyw" => "python",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "go" => "go",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "sh" | "bash" | "zsh" => "shell",
+        "ps1" | "psm1" => "powershell",
+        "bat" | "cmd" => "batch",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "json" => "json",
+        "toml" => "toml",
+        "yaml" | "yml" => "yaml",
+        "xml" => "xml",
+        "md" | "markdown" => "markdown",
+        "ini" | "cfg" => "ini",
+        _ => return None,
+    };
+    Some(language)
+}
+
+fn language_for_shebang(line: &str) -> Option<&'static str> {
+    let interpreter = line.strip_prefix("#!")?;
+    if interpreter.contains("python") {
+        Some("python")
+    } else if interpreter.contains("node") {
+        Some("javascript")
+    } else if interpreter.contains("sh") {
+        Some("shell")
+    } else {
+        None
+    }
+}
+
+fn zip_preview(path: &Path) -> Result<Preview, JooDockError> {
+    let file = File::open(path).map_err(|e| JooDockError::io(path, e))?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))
+        .map_err(|e| JooDockError::InvalidInput(format!("{}: {}", path.display(), e)))?;
+
+    let mut entries = Vec::new();
+    for i in 0..archive.len().min(MAX_ARCHIVE_ENTRIES) {
+        // Raw access reads the directory record without decompressing
+        if let Ok(entry) = archive.by_index_raw(i) {
+            entries.push(ArchiveEntry {
+                name: entry.name().to_string(),
+                size: entry.size(),
+                is_dir: entry.is_dir(),
+            });
+        }
+    }
+
+    Ok(Preview::Archive {
+        format: "zip".to_string(),
+        truncated: archive.len() > MAX_ARCHIVE_ENTRIES,
+        entries,
+    })
+}
+
+fn tar_preview(path: &Path, gzip: bool) -> Result<Preview, JooDockError> {
+    let file = File::open(path).map_err(|e| JooDockError::io(path, e))?;
+    let reader: Box<dyn Read> = if gzip {
+        Box::new(flate2::read::GzDecoder::new(BufReader::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    // Listing reads through every body, so stop after a fixed amount of data
+    let mut archive = tar::Archive::new(reader.take(TAR_READ_LIMIT));
+    let mut entries = Vec::new();
+    let mut truncated = false;
+    for entry in archive
+        .entries()
+        .map_err(|e| JooDockError::io(path, e))?
+        .filter_map(|e| e.ok())
+    {
+        if entries.len() == MAX_ARCHIVE_ENTRIES {
+            truncated = true;
+            break;
+        }
+        entries.push(ArchiveEntry {
+            name: entry
+                .path()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            size: entry.size(),
+            is_dir: entry.header().entry_type().is_dir(),
+        });
+    }
+    if archive.into_inner().limit() == 0 {
+        truncated = true;
+    }
+
+    Ok(Preview::Archive {
+        format: if gzip { "tar.gz" } else { "tar" }.to_string(),
+        entries,
+        truncated,
+    })
+}
+
+/// Counts `/Type /Page` objects, which is right for most unencrypted PDFs
+/// that do not keep their pages in compressed object streams.
+fn pdf_preview(path: &Path, len: u64) -> Result<Preview, JooDockError> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|f| f.take(PDF_READ_LIMIT).read_to_end(&mut bytes))
+        .map_err(|e| JooDockError::io(path, e))?;
+
+    let page = regex::bytes::Regex::new(r"/Type\s*/Page\b").expect("valid regex");
+    let pages = page.find_iter(&bytes).count();
+
+    Ok(Preview::Pdf {
+        pages: (pages > 0).then_some(pages),
+        truncated: len > PDF_READ_LIMIT,
+    })
+}
+
+fn directory_preview(path: &Path) -> Result<Preview, JooDockError> {
+    let mut entries: Vec<String> = fs::read_dir(path)
+        .map_err(|e| JooDockError::io(path, e))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    // Sort before truncating so the listing is the first entries by name,
+    // not whichever ones the file system happened to return first
+    entries.sort_by_key(|name| name.to_lowercase());
+    let truncated = entries.len() > MAX_ARCHIVE_ENTRIES;
+    entries.truncate(MAX_ARCHIVE_ENTRIES);
+
+    Ok(Preview::Directory { entries, truncated })
+}
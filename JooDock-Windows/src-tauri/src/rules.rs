@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::JooDockError;
+use crate::storage::FileGroup;
+
+/// What a routing rule looks at when a file is added without a group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleMatcher {
+    /// File extension, without the dot (e.g. `pdf`), case-insensitive
+    Extension { extensions: Vec<String> },
+    /// Folder the file lives under, e.g. `C:\Users\me\Documents\Invoices`
+    PathPrefix { prefix: String },
+    /// Glob matched against the file name, or the full path if it contains a separator
+    Glob { pattern: String },
+    /// Regular expression matched against the file name
+    NameRegex { pattern: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub id: String,
+    pub matcher: RuleMatcher,
+    #[serde(rename = "groupId")]
+    pub group_id: String,
+    pub enabled: bool,
+}
+
+impl RuleMatcher {
+    pub fn validate(&self) -> Result<(), JooDockError> {
+        match self {
+            RuleMatcher::Extension { extensions } => {
+                if extensions.iter().all(|e| e.trim().trim_start_matches('.').is_empty()) {
+                    return Err(JooDockError::InvalidInput(
+                        "At least one extension is required".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            RuleMatcher::PathPrefix { prefix } => {
+                if prefix.trim().is_empty() {
+                    return Err(JooDockError::InvalidInput(
+                        "Path prefix cannot be empty".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            RuleMatcher::Glob { pattern } => glob::Pattern::new(pattern)
+                .map(|_| ())
+                .map_err(|e| JooDockError::InvalidInput(e.to_string())),
+            RuleMatcher::NameRegex { pattern } => regex::Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|e| JooDockError::InvalidInput(e.to_string())),
+        }
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+
+        match self {
+            RuleMatcher::Extension { extensions } => {
+                let ext = Path::new(name)
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                !ext.is_empty()
+                    && extensions
+                        .iter()
+                        .any(|e| e.trim().trim_start_matches('.').eq_ignore_ascii_case(&ext))
+            }
+            RuleMatcher::PathPrefix { prefix } => {
+                let path = normalize(path);
+                let prefix = normalize(prefix);
+                let prefix = prefix.trim_end_matches('/');
+                path.starts_with(&format!("{}/", prefix))
+            }
+            RuleMatcher::Glob { pattern } => {
+                let options = glob::MatchOptions {
+                    case_sensitive: false,
+                    require_literal_separator: false,
+                    require_literal_leading_dot: false,
+                };
+                match glob::Pattern::new(&normalize(pattern)) {
+                    Ok(glob) if pattern.contains(['/', '\\']) => {
+                        glob.matches_with(&normalize(path), options)
+                    }
+                    Ok(glob) => glob.matches_with(&name.to_lowercase(), options),
+                    Err(_) => false,
+                }
+            }
+            RuleMatcher::NameRegex { pattern } => regex::Regex::new(pattern)
+                .map(|re| re.is_match(name))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Group for a newly added file: the first enabled rule that matches and
+/// points at an existing regular group wins.
+pub fn route(path: &str, rules: &[RoutingRule], groups: &[FileGroup]) -> Option<String> {
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter(|rule| {
+            groups
+                .iter()
+                .any(|g| g.id == rule.group_id && g.smart.is_none())
+        })
+        .find(|rule| rule.matcher.matches(path))
+        .map(|rule| rule.group_id.clone())
+}
+
+/// Windows paths compare case-insensitively and accept either separator.
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
@@ -0,0 +1,323 @@
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+
+use crate::commands::search;
+use crate::error::JooDockError;
+use crate::storage::{self, FileGroup, FileItem};
+
+const SMART_GROUP_LIMIT: usize = 50;
+const WALK_DEPTH: usize = 4;
+/// Quiet time after a change before smart groups are re-evaluated
+const WATCH_SETTLE_MS: u64 = 2000;
+const WATCH_MAX_WAIT_SECS: u64 = 10;
+/// How often the watched folders are matched against changed smart rules
+const WATCH_RULES_CHECK_SECS: u64 = 60;
+
+static RESULTS: OnceLock<Mutex<HashMap<String, Vec<FileItem>>>> = OnceLock::new();
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+/// How the contents of a smart group are computed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SmartRule {
+    /// A saved `search_files` query
+    Search { query: String },
+    /// Files in `folder` matching a glob such as `*.pdf` or `**/*.docx`
+    FolderGlob { folder: String, pattern: String },
+    /// Files modified in the last `days` days, in `folder` or the user folders
+    ModifiedWithin { folder: Option<String>, days: u32 },
+    /// Files opened most often through JooDock in the last `days` days
+    MostOpened { days: u32, limit: usize },
+}
+
+impl SmartRule {
+    pub fn validate(&self) -> Result<(), JooDockError> {
+        match self {
+            SmartRule::Search { query } if query.trim().is_empty() => Err(
+                JooDockError::InvalidInput("Search query cannot be empty".to_string()),
+            ),
+            SmartRule::FolderGlob { folder, pattern } => {
+                if !Path::new(folder).is_dir() {
+                    return Err(JooDockError::FileMissing(folder.into()));
+                }
+                check_pattern(pattern)?;
+                glob::Pattern::new(pattern)
+                    .map(|_| ())
+                    .map_err(|e| JooDockError::InvalidInput(e.to_string()))
+            }
+            SmartRule::ModifiedWithin { folder, days } => {
+                if let Some(folder) = folder {
+                    if !Path::new(folder).is_dir() {
+                        return Err(JooDockError::FileMissing(folder.into()));
+                    }
+                }
+                validate_days(*days)
+            }
+            SmartRule::MostOpened { days, .. } => validate_days(*days),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn evaluate(&self) -> Result<Vec<FileItem>, JooDockError> {
+        match self {
+            SmartRule::Search { query } => Ok(search::search(query, SMART_GROUP_LIMIT)),
+            SmartRule::FolderGlob { folder, pattern } => {
+                // Rules can also arrive through sync or import, unvalidated
+                check_pattern(pattern)?;
+                // Brackets or `*` in the folder name are not part of the pattern
+                let full = Path::new(&glob::Pattern::escape(folder)).join(pattern);
+                let paths = glob::glob(&full.to_string_lossy())
+                    .map_err(|e| JooDockError::InvalidInput(e.to_string()))?;
+                Ok(paths
+                    .filter_map(|p| p.ok())
+                    .take(SMART_GROUP_LIMIT)
+                    .map(|p| item_for_path(&p))
+                    .collect())
+            }
+            SmartRule::ModifiedWithin { folder, days } => {
+                let roots = match folder {
+                    Some(folder) => vec![folder.clone()],
+                    None => search::get_search_paths(),
+                };
+                Ok(modified_within(&roots, *days))
+            }
+            SmartRule::MostOpened { days, limit } => most_opened(*days, *limit),
+        }
+    }
+
+    /// Folders whose contents the rule is computed from.
+    fn folders(&self) -> Vec<PathBuf> {
+        match self {
+            SmartRule::FolderGlob { folder, .. }
+            | SmartRule::ModifiedWithin {
+                folder: Some(folder),
+                ..
+            } => vec![PathBuf::from(folder)],
+            SmartRule::Search { .. } | SmartRule::ModifiedWithin { folder: None, .. } => {
+                search::get_search_paths()
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .collect()
+            }
+            SmartRule::MostOpened { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Last computed contents of a smart group, if it has been evaluated yet.
+pub fn cached(id: &str) -> Option<Vec<FileItem>> {
+    results().lock().ok()?.get(id).cloned()
+}
+
+/// Evaluates one smart group and caches the result.
+pub fn refresh_group(group: &FileGroup) -> Result<Vec<FileItem>, JooDockError> {
+    let rule = group
+        .smart
+        .as_ref()
+        .ok_or_else(|| JooDockError::InvalidInput(format!("{} is not a smart group", group.name)))?;
+
+    let mut items = rule.evaluate()?;
+    for (position, item) in items.iter_mut().enumerate() {
+        item.group_id = Some(group.id.clone());
+        item.sort_order = position as i32;
+    }
+
+    if let Ok(mut results) = results().lock() {
+        results.insert(group.id.clone(), items.clone());
+    }
+    Ok(items)
+}
+
+/// Re-evaluates every smart group on a worker thread (when the popup is shown
+/// or a watched folder changes) and sends the results to the UI as
+/// `smart-groups-updated`.
+pub fn refresh_in_background(app: &AppHandle) {
+    if REFRESHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Ok(groups) = storage::load_groups() {
+            let updated: HashMap<String, Vec<FileItem>> = groups
+                .iter()
+                .filter(|g| g.smart.is_some())
+                .filter_map(|g| refresh_group(g).ok().map(|items| (g.id.clone(), items)))
+                .collect();
+            let _ = app.emit("smart-groups-updated", &updated);
+        }
+        REFRESHING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Watches the folders smart groups are computed from and re-evaluates the
+/// groups when something in them changes. The watched folders follow the smart
+/// rules, re-checked after every change and at least once a minute.
+pub fn start_watcher(app: AppHandle) {
+    let data_dir = storage::get_data_dir().ok();
+    let (changed, changes) = mpsc::channel();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        // Writes to JooDock's own stores and thumbnail cache are not content changes
+        let ours = |path: &PathBuf| data_dir.as_ref().is_some_and(|dir| path.starts_with(dir));
+        if event.is_ok_and(|event| !event.paths.iter().all(ours)) {
+            let _ = changed.send(());
+        }
+    });
+    let Ok(mut watcher) = watcher else {
+        return;
+    };
+
+    let mut watched: Vec<PathBuf> = Vec::new();
+    loop {
+        let folders = watched_folders();
+        if folders != watched {
+            for folder in &watched {
+                let _ = watcher.unwatch(folder);
+            }
+            for folder in &folders {
+                let _ = watcher.watch(folder, RecursiveMode::Recursive);
+            }
+            watched = folders;
+        }
+
+        match changes.recv_timeout(Duration::from_secs(WATCH_RULES_CHECK_SECS)) {
+            Ok(()) => {
+                // Let a burst of changes (e.g. copying a folder) settle first
+                let first = Instant::now();
+                while first.elapsed() < Duration::from_secs(WATCH_MAX_WAIT_SECS)
+                    && changes
+                        .recv_timeout(Duration::from_millis(WATCH_SETTLE_MS))
+                        .is_ok()
+                {}
+                refresh_in_background(&app);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+/// Existing folders read by the active profile's smart groups.
+fn watched_folders() -> Vec<PathBuf> {
+    let groups = storage::load_groups().unwrap_or_default();
+    let mut folders: Vec<PathBuf> = groups
+        .iter()
+        .filter_map(|g| g.smart.as_ref())
+        .flat_map(SmartRule::folders)
+        .filter(|folder| folder.is_dir())
+        .collect();
+    folders.sort();
+    folders.dedup();
+    folders
+}
+
+fn results() -> &'static Mutex<HashMap<String, Vec<FileItem>>> {
+    RESULTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// A glob must stay inside its folder: no `..` and no root or drive of its own.
+fn check_pattern(pattern: &str) -> Result<(), JooDockError> {
+    let leaves_folder = Path::new(pattern).components().any(|c| {
+        matches!(
+            c,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    });
+    if leaves_folder {
+        return Err(JooDockError::InvalidInput(format!(
+            "{} must be a pattern inside the folder, such as *.pdf",
+            pattern
+        )));
+    }
+    Ok(())
+}
+
+fn validate_days(days: u32) -> Result<(), JooDockError> {
+    if days == 0 || days > storage::MAX_DAYS {
+        return Err(JooDockError::InvalidInput(format!(
+            "Number of days must be between 1 and {}",
+            storage::MAX_DAYS
+        )));
+    }
+    Ok(())
+}
+
+fn item_for_path(path: &Path) -> FileItem {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+    FileItem::new(name, path.to_string_lossy().to_string(), None)
+}
+
+fn modified_within(roots: &[String], days: u32) -> Vec<FileItem> {
+    // Rules saved before days were bounded may reach past the epoch
+    let cutoff = SystemTime::now()
+        .checked_sub(Duration::from_secs(u64::from(days) * 24 * 60 * 60))
+        .unwrap_or(UNIX_EPOCH);
+    let mut found: Vec<(std::path::PathBuf, SystemTime)> = Vec::new();
+
+    for root in roots {
+        for entry in WalkDir::new(root)
+            .max_depth(WALK_DEPTH)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| !search::is_excluded(e.path(), &e.file_name().to_string_lossy()))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            if let Some(modified) = entry.metadata().ok().and_then(|m| m.modified().ok()) {
+                if modified >= cutoff {
+                    found.push((entry.into_path(), modified));
+                }
+            }
+        }
+    }
+
+    // Newest first
+    found.sort_by(|a, b| b.1.cmp(&a.1));
+    found
+        .into_iter()
+        .take(SMART_GROUP_LIMIT)
+        .map(|(path, _)| item_for_path(&path))
+        .collect()
+}
+
+fn most_opened(days: u32, limit: usize) -> Result<Vec<FileItem>, JooDockError> {
+    let cutoff = chrono::Utc::now()
+        .checked_sub_signed(chrono::Duration::days(i64::from(days)))
+        .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for record in storage::load_open_history()? {
+        let recent = chrono::DateTime::parse_from_rfc3339(&record.opened_at)
+            .map(|at| at >= cutoff)
+            .unwrap_or(false);
+        if recent {
+            *counts.entry(record.path).or_insert(0) += 1;
+        }
+    }
+
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    // Reuse the pinned item when there is one so names and ids stay familiar
+    let pinned = storage::load_files()?;
+    Ok(ranked
+        .into_iter()
+        .take(limit.min(SMART_GROUP_LIMIT))
+        .map(
+            |(path, _)| match pinned.iter().find(|f| f.path == path && !f.is_deleted()) {
+                Some(file) => file.clone(),
+                None => item_for_path(Path::new(&path)),
+            },
+        )
+        .collect())
+}
@@ -1,8 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread::{self, ThreadId};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::error::JooDockError;
+use crate::fileops::FileOperation;
+use crate::health::Fingerprint;
+use crate::janitor::CleanupEntry;
+use crate::journal::Journal;
+use crate::launch::Launch;
+use crate::rules::RoutingRule;
+use crate::smart::SmartRule;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileItem {
     pub id: String,
     pub name: String,
@@ -13,19 +29,167 @@ pub struct FileItem {
     pub added_at: String,
     #[serde(rename = "lastAccessedAt")]
     pub last_accessed_at: Option<String>,
+    /// Position within its group (or within the ungrouped list)
+    #[serde(rename = "sortOrder", default)]
+    pub sort_order: i32,
+    #[serde(default)]
+    pub kind: FileKind,
+    /// Size and file ID at the time the file was pinned, used to find it after a move
+    #[serde(default)]
+    pub fingerprint: Option<Fingerprint>,
+    /// For `Url` items `path` is the URL; for `Command` items it is the program
+    #[serde(rename = "itemType", default)]
+    pub item_type: ItemType,
+    /// Arguments and working directory of a `Command` item
+    #[serde(default)]
+    pub launch: Option<Launch>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Shown instead of `name`, and kept when the file is renamed
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// `#rrggbb` accent color
+    #[serde(default)]
+    pub color: Option<String>,
+    /// When the janitor clears a temporary pin
+    #[serde(rename = "expiresAt", default)]
+    pub expires_at: Option<String>,
+    /// Set while the item is in the archive; it keeps `group_id` for restoring
+    #[serde(rename = "deletedAt", default)]
+    pub deleted_at: Option<String>,
+    /// When the item last changed groups; `None` if it never did
+    #[serde(rename = "movedAt", default)]
+    pub moved_at: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What a pinned item opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemType {
+    #[default]
+    File,
+    Folder,
+    Url,
+    Command,
+}
+
+impl ItemType {
+    /// Whether `path` names a file or folder that can go missing, be moved, etc.
+    pub fn is_on_disk(self) -> bool {
+        matches!(self, ItemType::File | ItemType::Folder)
+    }
+}
+
+/// What a path pointed at when it was last looked at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileKind {
+    #[default]
+    File,
+    Directory,
+    Symlink,
+    Missing,
+}
+
+impl FileKind {
+    /// Looks at the path itself, so a link is reported as `Symlink` rather
+    /// than as whatever it points to.
+    pub fn of(path: &Path) -> Self {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => FileKind::Symlink,
+            Ok(metadata) if metadata.is_dir() => FileKind::Directory,
+            Ok(_) => FileKind::File,
+            Err(_) => FileKind::Missing,
+        }
+    }
+}
+
+impl FileItem {
+    pub fn new(name: String, path: String, group_id: Option<String>) -> Self {
+        FileItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind: FileKind::of(Path::new(&path)),
+            item_type: if Path::new(&path).is_dir() {
+                ItemType::Folder
+            } else {
+                ItemType::File
+            },
+            name,
+            path,
+            group_id,
+            added_at: chrono::Utc::now().to_rfc3339(),
+            last_accessed_at: None,
+            sort_order: 0,
+            fingerprint: None,
+            launch: None,
+            tags: Vec::new(),
+            note: None,
+            alias: None,
+            color: None,
+            expires_at: None,
+            deleted_at: None,
+            moved_at: None,
+        }
+    }
+
+    /// Whether the item was removed and only lives on in the archive.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// What the dock shows for the item: its alias, else its name.
+    pub fn display_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Puts the item in `group_id`, noting when it changed groups.
+    pub fn set_group(&mut self, group_id: Option<String>) {
+        if self.group_id != group_id {
+            self.moved_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        self.group_id = group_id;
+    }
+
+    /// Points the item at `path` after the file was moved or renamed. The
+    /// display name follows the file name unless the user changed it.
+    pub fn set_path(&mut self, path: String) {
+        let new_path = Path::new(&path);
+        let follows_file_name = Path::new(&self.path)
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy() == self.name.as_str());
+        if follows_file_name {
+            if let Some(name) = new_path.file_name() {
+                self.name = name.to_string_lossy().to_string();
+            }
+        }
+        self.kind = FileKind::of(new_path);
+        self.fingerprint = Fingerprint::of(new_path);
+        self.path = path;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileGroup {
     pub id: String,
     pub name: String,
     pub icon: String,
+    /// Position among the groups that share `parent_id`
     #[serde(rename = "sortOrder")]
     pub sort_order: i32,
+    #[serde(rename = "parentId", default)]
+    pub parent_id: Option<String>,
+    /// Set for smart groups, whose contents are computed instead of pinned
+    #[serde(default)]
+    pub smart: Option<SmartRule>,
     #[serde(rename = "isExpanded")]
     pub is_expanded: bool,
     #[serde(rename = "createdAt")]
     pub created_at: String,
+    /// `#rrggbb` accent color
+    #[serde(default)]
+    pub color: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +200,65 @@ pub struct Settings {
     pub hover_zone_height: f64,
     #[serde(rename = "hoverDelay")]
     pub hover_delay: f64,
+    /// Shared folder (OneDrive, Dropbox, network share...) to sync the dock through
+    #[serde(rename = "syncFolder", default)]
+    pub sync_folder: Option<String>,
+    /// Ask before running a pinned command
+    #[serde(rename = "confirmCommands", default = "default_confirm_commands")]
+    pub confirm_commands: bool,
+    #[serde(default)]
+    pub inbox: InboxSettings,
+    /// Days removed items stay in the archive; 0 keeps them until emptied by hand
+    #[serde(
+        rename = "archiveRetentionDays",
+        default = "default_archive_retention_days"
+    )]
+    pub archive_retention_days: u32,
+}
+
+fn default_confirm_commands() -> bool {
+    true
+}
+
+fn default_archive_retention_days() -> u32 {
+    30
+}
+
+/// A group whose items are cleared by the janitor once they reach an age.
+/// The same `action` applies to items whose `expiresAt` has passed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxSettings {
+    /// `None` leaves every group alone
+    #[serde(rename = "groupId", default)]
+    pub group_id: Option<String>,
+    #[serde(rename = "maxAgeDays", default = "default_inbox_max_age_days")]
+    pub max_age_days: u32,
+    #[serde(default)]
+    pub action: InboxAction,
+}
+
+impl Default for InboxSettings {
+    fn default() -> Self {
+        InboxSettings {
+            group_id: None,
+            max_age_days: default_inbox_max_age_days(),
+            action: InboxAction::default(),
+        }
+    }
+}
+
+fn default_inbox_max_age_days() -> u32 {
+    7
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InboxAction {
+    /// Move to the archive, where it can be restored
+    #[default]
+    Archive,
+    /// Delete for good
+    Remove,
 }
 
 impl Default for Settings {
@@ -44,87 +267,666 @@ impl Default for Settings {
             hover_zone_width: 300.0,
             hover_zone_height: 50.0,
             hover_delay: 0.3,
+            sync_folder: None,
+            confirm_commands: true,
+            inbox: InboxSettings::default(),
+            archive_retention_days: default_archive_retention_days(),
         }
     }
 }
 
-pub fn get_data_dir() -> PathBuf {
-    let base = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-    let dir = base.join("JooDock");
-    if !dir.exists() {
-        let _ = fs::create_dir_all(&dir);
+const DATA_DIR_ENV: &str = "JOODOCK_DATA_DIR";
+const DATA_DIR_ARG: &str = "--data-dir";
+const PORTABLE_MARKER: &str = "JooDock.portable";
+
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Picks up `--data-dir <path>` (or `--data-dir=<path>`) from the command line.
+/// Must run before the first call to `get_data_dir`.
+pub fn configure_data_dir(args: impl IntoIterator<Item = String>) {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = if arg == DATA_DIR_ARG {
+            args.next()
+        } else {
+            arg.strip_prefix("--data-dir=").map(str::to_string)
+        };
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            let _ = DATA_DIR_OVERRIDE.set(PathBuf::from(value));
+            return;
+        }
     }
-    dir
 }
 
-pub fn load_files() -> Vec<FileItem> {
-    let path = get_data_dir().join("files.json");
-    if path.exists() {
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(files) = serde_json::from_str(&content) {
-                return files;
-            }
+/// Resolves the data directory in priority order: `--data-dir`, the
+/// `JOODOCK_DATA_DIR` environment variable, portable mode (a `JooDock.portable`
+/// marker next to the executable keeps data in `data/` beside it), and finally
+/// `%LOCALAPPDATA%\JooDock`.
+fn resolve_data_dir() -> Result<PathBuf, JooDockError> {
+    if let Some(dir) = DATA_DIR_OVERRIDE.get() {
+        return Ok(dir.clone());
+    }
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        if exe_dir.join(PORTABLE_MARKER).exists() {
+            return Ok(exe_dir.join("data"));
         }
     }
-    Vec::new()
+    dirs::data_local_dir()
+        .map(|base| base.join("JooDock"))
+        .ok_or_else(|| {
+            JooDockError::InvalidInput(format!(
+                "Could not determine the local data directory; start JooDock with {} <path>",
+                DATA_DIR_ARG
+            ))
+        })
 }
 
-pub fn save_files(files: &[FileItem]) -> Result<(), String> {
-    let path = get_data_dir().join("files.json");
-    let content = serde_json::to_string_pretty(files).map_err(|e| e.to_string())?;
-    fs::write(path, content).map_err(|e| e.to_string())
+pub fn get_data_dir() -> Result<PathBuf, JooDockError> {
+    let dir = match DATA_DIR.get() {
+        Some(dir) => dir.clone(),
+        None => {
+            let dir = resolve_data_dir()?;
+            DATA_DIR.get_or_init(|| dir).clone()
+        }
+    };
+    if !dir.is_dir() {
+        fs::create_dir_all(&dir).map_err(|e| JooDockError::io(&dir, e))?;
+    }
+    Ok(dir)
 }
 
-pub fn load_groups() -> Vec<FileGroup> {
-    let path = get_data_dir().join("groups.json");
-    if path.exists() {
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(groups) = serde_json::from_str(&content) {
-                return groups;
-            }
+/// Reads a JSON file, returning `None` when it does not exist yet.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, JooDockError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(JooDockError::io(path, e)),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|source| JooDockError::CorruptData {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+/// Writes to a temporary file next to `path` and renames it into place, so a
+/// crash or full disk never leaves a half-written store behind.
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), JooDockError> {
+    let content = serde_json::to_string_pretty(value)?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, content).map_err(|e| JooDockError::io(&temp, e))?;
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        JooDockError::io(path, e)
+    })
+}
+
+/// Owner thread and how many times it has taken the lock.
+static STORE_LOCK: Mutex<(Option<ThreadId>, usize)> = Mutex::new((None, 0));
+static STORE_RELEASED: Condvar = Condvar::new();
+
+/// Keeps the dock stores locked until dropped; see [`lock`].
+pub struct StoreGuard(PhantomData<*const ()>);
+
+/// Serializes load-change-save cycles on the dock between commands, the sync
+/// thread and the janitor, so none of them saves over another's edit. The
+/// lock is reentrant: an edit holding it can call helpers that take it again.
+pub fn lock() -> StoreGuard {
+    let me = thread::current().id();
+    let mut state = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    while state.0.is_some_and(|owner| owner != me) {
+        state = STORE_RELEASED
+            .wait(state)
+            .unwrap_or_else(|e| e.into_inner());
+    }
+    state.0 = Some(me);
+    state.1 += 1;
+    StoreGuard(PhantomData)
+}
+
+impl Drop for StoreGuard {
+    fn drop(&mut self) {
+        let mut state = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        state.1 -= 1;
+        if state.1 == 0 {
+            state.0 = None;
+            STORE_RELEASED.notify_all();
         }
     }
-    // Return default groups
-    vec![
-        FileGroup {
-            id: uuid::Uuid::new_v4().to_string(),
-            name: "Work".to_string(),
-            icon: "briefcase".to_string(),
-            sort_order: 0,
-            is_expanded: true,
-            created_at: chrono::Utc::now().to_rfc3339(),
-        },
-        FileGroup {
-            id: uuid::Uuid::new_v4().to_string(),
-            name: "Personal".to_string(),
-            icon: "user".to_string(),
-            sort_order: 1,
-            is_expanded: true,
-            created_at: chrono::Utc::now().to_rfc3339(),
-        },
+}
+
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+const FILES_FILE: &str = "files.json";
+const GROUPS_FILE: &str = "groups.json";
+const SETTINGS_FILE: &str = "settings.json";
+const PROFILES_FILE: &str = "profiles.json";
+const OPEN_HISTORY_FILE: &str = "open_history.json";
+const RULES_FILE: &str = "rules.json";
+const OPEN_WITH_FILE: &str = "open_with.json";
+const JOURNAL_FILE: &str = "journal.json";
+/// Dock edits that can still be undone
+const JOURNAL_LIMIT: usize = 50;
+const JANITOR_LOG_FILE: &str = "janitor_log.json";
+const JANITOR_LOG_LIMIT: usize = 200;
+const FILE_OPERATIONS_FILE: &str = "file_operations.json";
+const EDIT_TIMES_FILE: &str = "edit_times.json";
+/// Upper bound for day counts in settings, a hundred years
+pub const MAX_DAYS: u32 = 36_500;
+const DEFAULT_GROUP_IDS: [&str; 2] = [
+    "00000000-0000-0000-0000-000000000001",
+    "00000000-0000-0000-0000-000000000002",
+];
+/// On-disk operations that can still be undone
+const FILE_OPERATIONS_LIMIT: usize = 10;
+const OPEN_WITH_LIMIT: usize = 5;
+const OPEN_HISTORY_DAYS: i64 = 30;
+/// Everything a profile keeps in its own directory apart from settings.
+/// `sync_state.json` is left out so a copy starts syncing from scratch.
+const PROFILE_FILES: &[&str] = &[
+    FILES_FILE,
+    GROUPS_FILE,
+    RULES_FILE,
+    OPEN_HISTORY_FILE,
+    JOURNAL_FILE,
+    FILE_OPERATIONS_FILE,
+    JANITOR_LOG_FILE,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    /// Whether this profile keeps its own settings.json instead of the shared one
+    #[serde(rename = "ownSettings")]
+    pub own_settings: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileList {
+    #[serde(rename = "activeId")]
+    pub active_id: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileList {
+    fn default() -> Self {
+        ProfileList {
+            active_id: DEFAULT_PROFILE_ID.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: "Default".to_string(),
+                own_settings: false,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+}
+
+impl ProfileList {
+    pub fn active(&self) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == self.active_id)
+    }
+}
+
+pub fn load_profiles() -> Result<ProfileList, JooDockError> {
+    let profiles = read_json(&get_data_dir()?.join(PROFILES_FILE))?;
+    Ok(profiles.unwrap_or_default())
+}
+
+pub fn save_profiles(profiles: &ProfileList) -> Result<(), JooDockError> {
+    write_json(&get_data_dir()?.join(PROFILES_FILE), profiles)
+}
+
+/// The default profile lives directly in the data directory so stores created
+/// before profiles existed keep working; every other profile gets a subfolder.
+pub fn profile_dir_for(id: &str) -> Result<PathBuf, JooDockError> {
+    let data_dir = get_data_dir()?;
+    if id == DEFAULT_PROFILE_ID {
+        return Ok(data_dir);
+    }
+    let dir = data_dir.join("profiles").join(id);
+    if !dir.is_dir() {
+        fs::create_dir_all(&dir).map_err(|e| JooDockError::io(&dir, e))?;
+    }
+    Ok(dir)
+}
+
+pub fn profile_dir() -> Result<PathBuf, JooDockError> {
+    profile_dir_for(&load_profiles()?.active_id)
+}
+
+fn settings_dir() -> Result<PathBuf, JooDockError> {
+    let profiles = load_profiles()?;
+    match profiles.active() {
+        Some(profile) if profile.own_settings => profile_dir_for(&profile.id),
+        _ => get_data_dir(),
+    }
+}
+
+/// Copies a profile's dock, rules and history (and its own settings, if any)
+/// into another profile.
+pub fn copy_profile_data(from: &Profile, to: &Profile) -> Result<(), JooDockError> {
+    let from_dir = profile_dir_for(&from.id)?;
+    let to_dir = profile_dir_for(&to.id)?;
+    for name in PROFILE_FILES {
+        let source = from_dir.join(name);
+        if source.is_file() {
+            fs::copy(&source, to_dir.join(name)).map_err(|e| JooDockError::io(&source, e))?;
+        }
+    }
+    if to.own_settings {
+        let settings_from = if from.own_settings {
+            from_dir
+        } else {
+            get_data_dir()?
+        };
+        let settings: Option<Settings> = read_json(&settings_from.join(SETTINGS_FILE))?;
+        write_json(&to_dir.join(SETTINGS_FILE), &settings.unwrap_or_default())?;
+    }
+    Ok(())
+}
+
+pub fn remove_profile_data(id: &str) -> Result<(), JooDockError> {
+    if id == DEFAULT_PROFILE_ID {
+        return Err(JooDockError::InvalidInput(
+            "The default profile cannot be deleted".to_string(),
+        ));
+    }
+    let dir = get_data_dir()?.join("profiles").join(id);
+    match fs::remove_dir_all(&dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(JooDockError::io(&dir, e)),
+    }
+}
+
+pub fn load_files_from(dir: &Path) -> Result<Vec<FileItem>, JooDockError> {
+    let files = read_json(&dir.join(FILES_FILE))?;
+    Ok(files.unwrap_or_default())
+}
+
+pub fn save_files_to(dir: &Path, files: &[FileItem]) -> Result<(), JooDockError> {
+    write_json(&dir.join(FILES_FILE), files)
+}
+
+/// Loads a profile's groups. A profile without a groups file gets the default
+/// groups, saved right away so their ids stay the same from call to call.
+pub fn load_groups_from(dir: &Path) -> Result<Vec<FileGroup>, JooDockError> {
+    if let Some(mut groups) = read_json::<Vec<FileGroup>>(&dir.join(GROUPS_FILE))? {
+        normalize_groups(&mut groups);
+        return Ok(groups);
+    }
+    let groups = default_groups();
+    save_groups_to(dir, &groups)?;
+    Ok(groups)
+}
+
+/// The groups a new profile starts with. Their ids are the same on every
+/// device, so two devices syncing for the first time share them.
+pub fn default_groups() -> Vec<FileGroup> {
+    [
+        (DEFAULT_GROUP_IDS[0], "Work", "briefcase"),
+        (DEFAULT_GROUP_IDS[1], "Personal", "user"),
     ]
+    .into_iter()
+    .enumerate()
+    .map(|(sort_order, (id, name, icon))| FileGroup {
+        id: id.to_string(),
+        name: name.to_string(),
+        icon: icon.to_string(),
+        sort_order: sort_order as i32,
+        parent_id: None,
+        smart: None,
+        is_expanded: true,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        color: None,
+    })
+    .collect()
 }
 
-pub fn save_groups(groups: &[FileGroup]) -> Result<(), String> {
-    let path = get_data_dir().join("groups.json");
-    let content = serde_json::to_string_pretty(groups).map_err(|e| e.to_string())?;
-    fs::write(path, content).map_err(|e| e.to_string())
+/// Whether `group` is one of the default groups, unchanged except for when
+/// it was created.
+pub fn is_untouched_default(group: &FileGroup) -> bool {
+    default_groups().into_iter().any(|default| {
+        FileGroup {
+            created_at: group.created_at.clone(),
+            ..default
+        } == *group
+    })
 }
 
-pub fn load_settings() -> Settings {
-    let path = get_data_dir().join("settings.json");
-    if path.exists() {
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(settings) = serde_json::from_str(&content) {
-                return settings;
+/// Repairs the group tree and its ordering: parents that are missing or would
+/// form a cycle are dropped (the group becomes top-level), then groups are
+/// sorted by `sort_order` and renumbered 0..n among their siblings, closing
+/// gaps and resolving duplicates left by older versions (ties keep file order).
+pub fn normalize_groups(groups: &mut [FileGroup]) {
+    for i in 0..groups.len() {
+        let broken = match &groups[i].parent_id {
+            Some(parent_id) => {
+                !groups.iter().any(|g| &g.id == parent_id) || is_in_cycle(groups, i)
             }
+            None => false,
+        };
+        if broken {
+            groups[i].parent_id = None;
+        }
+    }
+
+    groups.sort_by_key(|g| g.sort_order);
+    let mut next: HashMap<Option<String>, i32> = HashMap::new();
+    for group in groups.iter_mut() {
+        let position = next.entry(group.parent_id.clone()).or_insert(0);
+        group.sort_order = *position;
+        *position += 1;
+    }
+}
+
+/// Whether following parents from `groups[index]` leads back to it. A group
+/// that merely hangs below someone else's cycle is not part of one.
+fn is_in_cycle(groups: &[FileGroup], index: usize) -> bool {
+    let id = &groups[index].id;
+    let mut current = groups[index].parent_id.as_ref();
+    for _ in 0..groups.len() {
+        match current {
+            Some(parent_id) if parent_id == id => return true,
+            Some(parent_id) => {
+                current = groups
+                    .iter()
+                    .find(|g| &g.id == parent_id)
+                    .and_then(|g| g.parent_id.as_ref());
+            }
+            None => return false,
+        }
+    }
+    false
+}
+
+pub fn save_groups_to(dir: &Path, groups: &[FileGroup]) -> Result<(), JooDockError> {
+    write_json(&dir.join(GROUPS_FILE), groups)
+}
+
+pub fn load_files() -> Result<Vec<FileItem>, JooDockError> {
+    load_files_from(&profile_dir()?)
+}
+
+/// Saves the active profile's files and notes which ones were edited.
+pub fn save_files(files: &[FileItem]) -> Result<(), JooDockError> {
+    let dir = profile_dir()?;
+    let edited = edited_ids(&load_files_from(&dir)?, files, |f| &f.id);
+    save_files_to(&dir, files)?;
+    stamp_edits(&dir, edited, |times| &mut times.files)
+}
+
+pub fn load_groups() -> Result<Vec<FileGroup>, JooDockError> {
+    load_groups_from(&profile_dir()?)
+}
+
+/// Saves the active profile's groups and notes which ones were edited.
+pub fn save_groups(groups: &[FileGroup]) -> Result<(), JooDockError> {
+    let dir = profile_dir()?;
+    let edited = edited_ids(&load_groups_from(&dir)?, groups, |g| &g.id);
+    save_groups_to(&dir, groups)?;
+    stamp_edits(&dir, edited, |times| &mut times.groups)
+}
+
+/// When each file and group was last edited on this device. Sync orders edits
+/// by these rather than by when they were synced, and clears them once the
+/// edits are in its change log.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EditTimes {
+    #[serde(default)]
+    pub files: HashMap<String, DateTime<Utc>>,
+    #[serde(default)]
+    pub groups: HashMap<String, DateTime<Utc>>,
+}
+
+impl EditTimes {
+    /// Edit time of the record a sync change key (`"file"` or `"group"`) points at.
+    pub fn of(&self, entity: &str, id: &str) -> Option<DateTime<Utc>> {
+        match entity {
+            "file" => self.files.get(id).copied(),
+            "group" => self.groups.get(id).copied(),
+            _ => None,
+        }
+    }
+
+    /// Drops the times that `pushed` holds too, keeping those of records
+    /// edited again since.
+    pub fn forget(&mut self, pushed: &EditTimes) {
+        self.files.retain(|id, at| pushed.files.get(id) != Some(at));
+        self.groups.retain(|id, at| pushed.groups.get(id) != Some(at));
+    }
+}
+
+pub fn load_edit_times(dir: &Path) -> Result<EditTimes, JooDockError> {
+    let times = read_json(&dir.join(EDIT_TIMES_FILE))?;
+    Ok(times.unwrap_or_default())
+}
+
+pub fn save_edit_times(dir: &Path, times: &EditTimes) -> Result<(), JooDockError> {
+    write_json(&dir.join(EDIT_TIMES_FILE), times)
+}
+
+/// Ids of the records that were added, changed or removed between `old` and `new`.
+fn edited_ids<T: PartialEq>(old: &[T], new: &[T], id: impl Fn(&T) -> &String) -> Vec<String> {
+    let changed = new
+        .iter()
+        .filter(|n| old.iter().find(|o| id(o) == id(n)) != Some(*n))
+        .map(&id);
+    let removed = old
+        .iter()
+        .filter(|o| !new.iter().any(|n| id(n) == id(o)))
+        .map(&id);
+    changed.chain(removed).cloned().collect()
+}
+
+fn stamp_edits(
+    dir: &Path,
+    ids: Vec<String>,
+    times_of: impl FnOnce(&mut EditTimes) -> &mut HashMap<String, DateTime<Utc>>,
+) -> Result<(), JooDockError> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let mut times = load_edit_times(dir)?;
+    let now = Utc::now();
+    let stamps = times_of(&mut times);
+    for id in ids {
+        stamps.insert(id, now);
+    }
+    save_edit_times(dir, &times)
+}
+
+pub fn load_settings() -> Result<Settings, JooDockError> {
+    let settings = read_json(&settings_dir()?.join(SETTINGS_FILE))?;
+    Ok(settings.unwrap_or_default())
+}
+
+/// Saves the settings after checking the day counts the janitor computes with.
+pub fn save_settings(settings: &Settings) -> Result<(), JooDockError> {
+    for (name, days) in [
+        ("maxAgeDays", settings.inbox.max_age_days),
+        ("archiveRetentionDays", settings.archive_retention_days),
+    ] {
+        if days > MAX_DAYS {
+            return Err(JooDockError::InvalidInput(format!(
+                "{} must be at most {} days",
+                name, MAX_DAYS
+            )));
         }
     }
-    Settings::default()
+    write_json(&settings_dir()?.join(SETTINGS_FILE), settings)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenRecord {
+    pub path: String,
+    #[serde(rename = "openedAt")]
+    pub opened_at: String,
+}
+
+pub fn load_open_history() -> Result<Vec<OpenRecord>, JooDockError> {
+    let history = read_json(&profile_dir()?.join(OPEN_HISTORY_FILE))?;
+    Ok(history.unwrap_or_default())
+}
+
+/// Remembers that `path` was opened, keeping the last 30 days of history.
+/// The history only feeds smart groups, so a failed write is logged instead
+/// of failing an open that already happened.
+pub fn record_open(path: &str) {
+    if let Err(e) = append_open_record(path) {
+        eprintln!("Could not record that {} was opened: {}", path, e);
+    }
+}
+
+fn append_open_record(path: &str) -> Result<(), JooDockError> {
+    let now = chrono::Utc::now();
+    let cutoff = now - chrono::Duration::days(OPEN_HISTORY_DAYS);
+
+    let mut history = load_open_history()?;
+    history.retain(|record| {
+        chrono::DateTime::parse_from_rfc3339(&record.opened_at)
+            .map(|at| at >= cutoff)
+            .unwrap_or(false)
+    });
+    history.push(OpenRecord {
+        path: path.to_string(),
+        opened_at: now.to_rfc3339(),
+    });
+
+    write_json(&profile_dir()?.join(OPEN_HISTORY_FILE), &history)
+}
+
+/// Applications used through "Open with", per extension, most recent first.
+/// Kept in the data root because application paths belong to this machine.
+pub fn load_open_with() -> Result<HashMap<String, Vec<String>>, JooDockError> {
+    let apps = read_json(&get_data_dir()?.join(OPEN_WITH_FILE))?;
+    Ok(apps.unwrap_or_default())
+}
+
+pub fn remember_open_with(extension: &str, application: &str) -> Result<(), JooDockError> {
+    let mut apps = load_open_with()?;
+    let list = apps.entry(extension.to_string()).or_default();
+    list.retain(|a| !a.eq_ignore_ascii_case(application));
+    list.insert(0, application.to_string());
+    list.truncate(OPEN_WITH_LIMIT);
+
+    write_json(&get_data_dir()?.join(OPEN_WITH_FILE), &apps)
+}
+
+pub fn load_journal() -> Result<Journal, JooDockError> {
+    let journal = read_json(&profile_dir()?.join(JOURNAL_FILE))?;
+    Ok(journal.unwrap_or_default())
+}
+
+/// Saves the undo/redo journal, dropping the oldest entries beyond the limit.
+pub fn save_journal(journal: &Journal) -> Result<(), JooDockError> {
+    let mut journal = journal.clone();
+    for entries in [&mut journal.undo, &mut journal.redo] {
+        let excess = entries.len().saturating_sub(JOURNAL_LIMIT);
+        entries.drain(..excess);
+    }
+    write_json(&profile_dir()?.join(JOURNAL_FILE), &journal)
+}
+
+pub fn load_file_operations() -> Result<Vec<FileOperation>, JooDockError> {
+    let operations = read_json(&profile_dir()?.join(FILE_OPERATIONS_FILE))?;
+    Ok(operations.unwrap_or_default())
+}
+
+/// Saves the undo stack, dropping the oldest operations beyond the limit.
+pub fn save_file_operations(operations: &[FileOperation]) -> Result<(), JooDockError> {
+    let start = operations.len().saturating_sub(FILE_OPERATIONS_LIMIT);
+    write_json(&profile_dir()?.join(FILE_OPERATIONS_FILE), &operations[start..])
+}
+
+pub fn load_janitor_log() -> Result<Vec<CleanupEntry>, JooDockError> {
+    let log = read_json(&profile_dir()?.join(JANITOR_LOG_FILE))?;
+    Ok(log.unwrap_or_default())
+}
+
+/// Saves what the janitor cleared, dropping the oldest entries beyond the limit.
+pub fn save_janitor_log(log: &[CleanupEntry]) -> Result<(), JooDockError> {
+    let start = log.len().saturating_sub(JANITOR_LOG_LIMIT);
+    write_json(&profile_dir()?.join(JANITOR_LOG_FILE), &log[start..])
+}
+
+pub fn load_rules() -> Result<Vec<RoutingRule>, JooDockError> {
+    let rules = read_json(&profile_dir()?.join(RULES_FILE))?;
+    Ok(rules.unwrap_or_default())
+}
+
+pub fn save_rules(rules: &[RoutingRule]) -> Result<(), JooDockError> {
+    write_json(&profile_dir()?.join(RULES_FILE), rules)
 }
 
-pub fn save_settings(settings: &Settings) -> Result<(), String> {
-    let path = get_data_dir().join("settings.json");
-    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    fs::write(path, content).map_err(|e| e.to_string())
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(id: &str, parent_id: Option<&str>, sort_order: i32) -> FileGroup {
+        FileGroup {
+            id: id.to_string(),
+            name: id.to_string(),
+            icon: "folder".to_string(),
+            sort_order,
+            parent_id: parent_id.map(String::from),
+            smart: None,
+            is_expanded: true,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            color: None,
+        }
+    }
+
+    fn parent_of<'a>(groups: &'a [FileGroup], id: &str) -> Option<&'a str> {
+        groups
+            .iter()
+            .find(|g| g.id == id)
+            .and_then(|g| g.parent_id.as_deref())
+    }
+
+    #[test]
+    fn normalize_breaks_parent_cycles_only() {
+        // a -> b -> a is a cycle; c hangs off it and d points at a missing group
+        let mut groups = vec![
+            group("a", Some("b"), 0),
+            group("b", Some("a"), 0),
+            group("c", Some("a"), 3),
+            group("d", Some("gone"), 5),
+            group("e", None, 2),
+        ];
+        normalize_groups(&mut groups);
+
+        // The first group of the cycle becomes top-level, the rest keeps its parents
+        assert_eq!(parent_of(&groups, "a"), None);
+        assert_eq!(parent_of(&groups, "b"), Some("a"));
+        assert_eq!(parent_of(&groups, "c"), Some("a"));
+        assert_eq!(parent_of(&groups, "d"), None);
+
+        let mut top: Vec<(&str, i32)> = groups
+            .iter()
+            .filter(|g| g.parent_id.is_none())
+            .map(|g| (g.id.as_str(), g.sort_order))
+            .collect();
+        top.sort_by_key(|&(_, order)| order);
+        assert_eq!(top, [("a", 0), ("e", 1), ("d", 2)]);
+        let mut children: Vec<(&str, i32)> = groups
+            .iter()
+            .filter(|g| g.parent_id.as_deref() == Some("a"))
+            .map(|g| (g.id.as_str(), g.sort_order))
+            .collect();
+        children.sort_by_key(|&(_, order)| order);
+        assert_eq!(children, [("b", 0), ("c", 1)]);
+    }
+}
@@ -0,0 +1,460 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::changes::{Change, Dock};
+use crate::error::JooDockError;
+use crate::storage;
+
+const SYNC_INTERVAL_SECS: u64 = 60;
+const SYNC_STATE_FILE: &str = "sync_state.json";
+const DEVICE_FILE: &str = "device.json";
+
+/// Keeps two syncs (background and manual) from writing the same log at once.
+static SYNCING: Mutex<()> = Mutex::new(());
+
+/// One entry of a device's change log in the shared folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedChange {
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub device: String,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+/// What this device had in common with the shared folder after the last sync.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    base: Dock,
+    /// Highest log `seq` already applied, per remote device
+    seen: HashMap<String, u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Device {
+    id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncConflict {
+    pub entity: String,
+    pub id: String,
+    #[serde(rename = "winnerDevice")]
+    pub winner_device: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub conflicts: Vec<SyncConflict>,
+    /// Device logs that could not be read (e.g. still being written by the sync client)
+    #[serde(rename = "skippedLogs")]
+    pub skipped_logs: Vec<String>,
+    /// Whether the local store changed and the UI should reload
+    pub changed: bool,
+}
+
+/// Stable id for this installation, used to name its change log.
+pub fn device_id() -> Result<String, JooDockError> {
+    let path = storage::get_data_dir()?.join(DEVICE_FILE);
+    if let Some(device) = storage::read_json::<Device>(&path)? {
+        return Ok(device.id);
+    }
+    let device = Device {
+        id: uuid::Uuid::new_v4().to_string(),
+    };
+    storage::write_json(&path, &device)?;
+    Ok(device.id)
+}
+
+/// Syncs the active profile with the folder from settings, if one is configured.
+pub fn sync_active_profile() -> Result<Option<SyncReport>, JooDockError> {
+    // Resolve both folders at once, so a profile switch cannot pair them up wrong
+    let (folder, profile_id) = {
+        let _lock = storage::lock();
+        (
+            storage::load_settings()?.sync_folder,
+            storage::load_profiles()?.active_id,
+        )
+    };
+    let folder = match folder {
+        Some(folder) if !folder.trim().is_empty() => folder,
+        _ => return Ok(None),
+    };
+    let sync_dir = Path::new(&folder).join("JooDock").join(&profile_id);
+    let store_dir = storage::profile_dir_for(&profile_id)?;
+    sync_dirs(&store_dir, &sync_dir, &device_id()?).map(Some)
+}
+
+/// Two-way sync between a local store directory and a shared sync directory.
+///
+/// Every device only ever writes its own `<device>.json` change log, so file
+/// sync clients never have to merge a file. The merged dock is rebuilt by
+/// replaying all logs in the order the edits were made; concurrent edits to
+/// the same file or group resolve to the one made last. The store lock is
+/// only held to read and write the local store, never while the shared folder
+/// is busy, and edits made in the meantime are kept for the next sync.
+pub fn sync_dirs(
+    store_dir: &Path,
+    sync_dir: &Path,
+    device_id: &str,
+) -> Result<SyncReport, JooDockError> {
+    let _syncing = SYNCING.lock().unwrap_or_else(|e| e.into_inner());
+    let state_path = store_dir.join(SYNC_STATE_FILE);
+    let (mut state, local, edit_times) = {
+        let _lock = storage::lock();
+        let state: SyncState = storage::read_json(&state_path)?.unwrap_or_default();
+        let local = Dock {
+            files: storage::load_files_from(store_dir)?,
+            groups: storage::load_groups_from(store_dir)?,
+        };
+        (state, local, storage::load_edit_times(store_dir)?)
+    };
+
+    fs::create_dir_all(sync_dir).map_err(|e| JooDockError::io(sync_dir, e))?;
+
+    // Push local edits made since the last sync
+    let own_log_path = sync_dir.join(format!("{}.json", device_id));
+    let first_sync = !own_log_path.exists();
+    if first_sync {
+        // First sync, or our log was removed from the shared folder: push everything
+        state.base = Dock::default();
+    }
+    let mut own_log: Vec<LoggedChange> = storage::read_json(&own_log_path)?.unwrap_or_default();
+    let local_changes: Vec<Change> = state
+        .base
+        .diff(&local)
+        .into_iter()
+        // Every device starts with the default groups, so only edits to them travel
+        .filter(|change| match change {
+            Change::PutGroup { group } => {
+                !storage::is_untouched_default(group)
+                    || state.base.groups.iter().any(|g| g.id == group.id)
+            }
+            _ => true,
+        })
+        .collect();
+    let mut next_seq = own_log.last().map(|c| c.seq + 1).unwrap_or(0);
+    let now = Utc::now();
+    for change in &local_changes {
+        let (entity, id) = change.key();
+        own_log.push(LoggedChange {
+            seq: next_seq,
+            at: edit_times.of(entity, id).unwrap_or(now),
+            device: device_id.to_string(),
+            change: change.clone(),
+        });
+        next_seq += 1;
+    }
+    let logged = own_log.len();
+    compact(&mut own_log);
+    if first_sync || !local_changes.is_empty() || own_log.len() < logged {
+        storage::write_json(&own_log_path, &own_log)?;
+    }
+
+    let mut report = SyncReport {
+        pushed: local_changes.len(),
+        ..Default::default()
+    };
+
+    // Pull every other device's log
+    let mut all_changes = own_log;
+    let mut remote_keys: HashSet<(&'static str, String)> = HashSet::new();
+    let mut seen = state.seen.clone();
+    let mut pulled = 0;
+    let entries = fs::read_dir(sync_dir).map_err(|e| JooDockError::io(sync_dir, e))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let device = match path.file_stem() {
+            Some(stem) if path.extension().map(|e| e == "json").unwrap_or(false) => {
+                stem.to_string_lossy().to_string()
+            }
+            _ => continue,
+        };
+        if device == device_id {
+            continue;
+        }
+
+        let log: Vec<LoggedChange> = match storage::read_json(&path) {
+            Ok(Some(log)) => log,
+            Ok(None) => continue,
+            Err(_) => {
+                report.skipped_logs.push(device);
+                continue;
+            }
+        };
+
+        let last_seen = seen.get(&device).copied();
+        for change in log.iter().filter(|c| last_seen.is_none_or(|s| c.seq > s)) {
+            let (entity, id) = change.change.key();
+            remote_keys.insert((entity, id.to_string()));
+            pulled += 1;
+        }
+        if let Some(last) = log.iter().map(|c| c.seq).max() {
+            seen.insert(device, last);
+        }
+        all_changes.extend(log);
+    }
+    if !report.skipped_logs.is_empty() {
+        // Without a device's log its items would look unpinned. Our edits are
+        // pushed; keeping base and edit times pushes them again, unchanged.
+        return Ok(report);
+    }
+    report.pulled = pulled;
+    state.seen = seen;
+
+    // Replay everything onto the default groups this device has not touched;
+    // later writes win, ties broken by device id
+    all_changes.sort_by(|a, b| (a.at, &a.device, a.seq).cmp(&(b.at, &b.device, b.seq)));
+
+    let mut winners: HashMap<(&'static str, String), String> = HashMap::new();
+    let mut merged = Dock {
+        files: Vec::new(),
+        groups: local
+            .groups
+            .iter()
+            .filter(|g| storage::is_untouched_default(g))
+            .cloned()
+            .collect(),
+    };
+    for logged in all_changes {
+        let (entity, id) = logged.change.key();
+        winners.insert((entity, id.to_string()), logged.device.clone());
+        merged.apply(logged.change);
+    }
+    merged.detach_orphans();
+
+    for change in &local_changes {
+        let (entity, id) = change.key();
+        let key = (entity, id.to_string());
+        if remote_keys.contains(&key) {
+            report.conflicts.push(SyncConflict {
+                entity: entity.to_string(),
+                id: id.to_string(),
+                winner_device: winners.get(&key).cloned().unwrap_or_default(),
+            });
+        }
+    }
+
+    // Edits made while the shared folder was read go on top of the merge
+    let _lock = storage::lock();
+    let current = Dock {
+        files: storage::load_files_from(store_dir)?,
+        groups: storage::load_groups_from(store_dir)?,
+    };
+    let mut updated = merged.clone();
+    for change in local.diff(&current) {
+        updated.apply(change);
+    }
+    updated.detach_orphans();
+
+    report.changed = updated != current;
+    if report.changed {
+        storage::save_groups_to(store_dir, &updated.groups)?;
+        storage::save_files_to(store_dir, &updated.files)?;
+    }
+    state.base = merged;
+    storage::write_json(&state_path, &state)?;
+    // The log now carries the edit times
+    let mut times = storage::load_edit_times(store_dir)?;
+    times.forget(&edit_times);
+    storage::save_edit_times(store_dir, &times)?;
+
+    Ok(report)
+}
+
+/// Keeps only the newest entry for each record. Replay lets the newest edit
+/// of a record win, so the older ones of the same device never matter again.
+fn compact(log: &mut Vec<LoggedChange>) {
+    let mut newest: HashMap<(&'static str, String), (DateTime<Utc>, u64)> = HashMap::new();
+    for logged in log.iter() {
+        let (entity, id) = logged.change.key();
+        let entry = newest
+            .entry((entity, id.to_string()))
+            .or_insert((logged.at, logged.seq));
+        *entry = (*entry).max((logged.at, logged.seq));
+    }
+    let keep: HashSet<u64> = newest.into_values().map(|(_, seq)| seq).collect();
+    log.retain(|logged| keep.contains(&logged.seq));
+}
+
+/// Periodically syncs in the background and tells the UI when the dock changed.
+pub fn start_background_sync(app: AppHandle) {
+    loop {
+        if let Ok(Some(report)) = sync_active_profile() {
+            if report.changed {
+                let _ = app.emit("sync-completed", &report);
+            }
+        }
+        std::thread::sleep(Duration::from_secs(SYNC_INTERVAL_SECS));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{EditTimes, FileItem};
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("joodock-sync-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn remove_dirs(dirs: &[&Path]) {
+        for dir in dirs {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    fn log_of(sync_dir: &Path, device: &str) -> Vec<LoggedChange> {
+        storage::read_json(&sync_dir.join(format!("{}.json", device)))
+            .unwrap()
+            .unwrap()
+    }
+
+    fn group_names(store_dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = storage::load_groups_from(store_dir)
+            .unwrap()
+            .into_iter()
+            .map(|g| g.name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn file(name: &str) -> FileItem {
+        FileItem::new(name.to_string(), format!("C:\\Docs\\{}", name), None)
+    }
+
+    /// Saves `file` on a device as edited `minutes_ago`.
+    fn edit(store_dir: &Path, file: &FileItem, minutes_ago: i64) {
+        storage::save_files_to(store_dir, std::slice::from_ref(file)).unwrap();
+        let mut times = EditTimes::default();
+        times.files.insert(
+            file.id.clone(),
+            Utc::now() - chrono::Duration::minutes(minutes_ago),
+        );
+        storage::save_edit_times(store_dir, &times).unwrap();
+    }
+
+    #[test]
+    fn round_trip_between_two_devices() {
+        let (shared, a, b) = (temp_dir("shared"), temp_dir("a"), temp_dir("b"));
+        let report = file("report.docx");
+        storage::save_files_to(&a, std::slice::from_ref(&report)).unwrap();
+
+        let pushed = sync_dirs(&a, &shared, "a").unwrap();
+        assert_eq!(pushed.pushed, 1);
+        assert!(!pushed.changed);
+
+        let pulled = sync_dirs(&b, &shared, "b").unwrap();
+        assert_eq!(pulled.pulled, 1);
+        assert!(pulled.changed);
+        assert_eq!(storage::load_files_from(&b).unwrap(), vec![report]);
+
+        // Unpinning on b unpins on a
+        storage::save_files_to(&b, &[]).unwrap();
+        assert_eq!(sync_dirs(&b, &shared, "b").unwrap().pushed, 1);
+        assert!(sync_dirs(&a, &shared, "a").unwrap().changed);
+        assert!(storage::load_files_from(&a).unwrap().is_empty());
+
+        // Nothing left to exchange
+        let idle = sync_dirs(&a, &shared, "a").unwrap();
+        assert_eq!((idle.pushed, idle.pulled, idle.changed), (0, 0, false));
+
+        remove_dirs(&[&shared, &a, &b]);
+    }
+
+    #[test]
+    fn conflicting_edits_resolve_to_the_later_edit() {
+        let (shared, a, b) = (temp_dir("shared"), temp_dir("a"), temp_dir("b"));
+        let original = file("notes.txt");
+        edit(&a, &original, 20);
+        sync_dirs(&a, &shared, "a").unwrap();
+        sync_dirs(&b, &shared, "b").unwrap();
+
+        // a renames first, b renames later, but b syncs first
+        let mut on_a = original.clone();
+        on_a.name = "from a".to_string();
+        edit(&a, &on_a, 10);
+        let mut on_b = original.clone();
+        on_b.name = "from b".to_string();
+        edit(&b, &on_b, 5);
+
+        sync_dirs(&b, &shared, "b").unwrap();
+        let report = sync_dirs(&a, &shared, "a").unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].winner_device, "b");
+        assert_eq!(storage::load_files_from(&a).unwrap()[0].name, "from b");
+
+        sync_dirs(&b, &shared, "b").unwrap();
+        assert_eq!(storage::load_files_from(&b).unwrap()[0].name, "from b");
+
+        remove_dirs(&[&shared, &a, &b]);
+    }
+
+    #[test]
+    fn default_groups_are_shared_not_duplicated() {
+        let (shared, a, b) = (temp_dir("shared"), temp_dir("a"), temp_dir("b"));
+        sync_dirs(&a, &shared, "a").unwrap();
+        sync_dirs(&b, &shared, "b").unwrap();
+        sync_dirs(&a, &shared, "a").unwrap();
+        assert_eq!(group_names(&a), ["Personal", "Work"]);
+        assert_eq!(group_names(&b), ["Personal", "Work"]);
+
+        // Edits to a default group still travel
+        let mut groups = storage::load_groups_from(&b).unwrap();
+        groups[0].name = "Office".to_string();
+        storage::save_groups_to(&b, &groups).unwrap();
+        assert_eq!(sync_dirs(&b, &shared, "b").unwrap().pushed, 1);
+        sync_dirs(&a, &shared, "a").unwrap();
+        assert_eq!(group_names(&a), ["Office", "Personal"]);
+
+        remove_dirs(&[&shared, &a, &b]);
+    }
+
+    #[test]
+    fn unreadable_log_leaves_the_store_alone() {
+        let (shared, a, b) = (temp_dir("shared"), temp_dir("a"), temp_dir("b"));
+        let report = file("report.docx");
+        storage::save_files_to(&a, std::slice::from_ref(&report)).unwrap();
+        sync_dirs(&a, &shared, "a").unwrap();
+        sync_dirs(&b, &shared, "b").unwrap();
+
+        // a's log is caught mid-write
+        fs::write(shared.join("a.json"), "[{").unwrap();
+        let skipped = sync_dirs(&b, &shared, "b").unwrap();
+        assert_eq!(skipped.skipped_logs, ["a"]);
+        assert!(!skipped.changed);
+        assert_eq!(storage::load_files_from(&b).unwrap(), vec![report]);
+
+        remove_dirs(&[&shared, &a, &b]);
+    }
+
+    #[test]
+    fn own_log_keeps_the_newest_entry_per_record() {
+        let (shared, a) = (temp_dir("shared"), temp_dir("a"));
+        let mut notes = file("notes.txt");
+        for (minutes_ago, name) in [(3, "one"), (2, "two"), (1, "three")] {
+            notes.name = name.to_string();
+            edit(&a, &notes, minutes_ago);
+            sync_dirs(&a, &shared, "a").unwrap();
+        }
+
+        let log = log_of(&shared, "a");
+        assert_eq!(log.len(), 1);
+        assert!(matches!(&log[0].change, Change::PutFile { file } if file.name == "three"));
+
+        remove_dirs(&[&shared, &a]);
+    }
+}
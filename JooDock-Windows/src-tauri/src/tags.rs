@@ -0,0 +1,70 @@
+use crate::error::JooDockError;
+use crate::storage::FileItem;
+
+const TAG_PREFIX: &str = "tag:";
+pub const NOTE_LIMIT: usize = 500;
+
+/// Checks a tag typed by the user and returns it trimmed. Tags cannot contain
+/// spaces so that `tag:` filters stay one word.
+pub fn validate_tag(tag: &str) -> Result<String, JooDockError> {
+    let tag = tag.trim().trim_start_matches('#');
+    if tag.is_empty() {
+        return Err(JooDockError::InvalidInput(
+            "Tag cannot be empty".to_string(),
+        ));
+    }
+    if tag.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(JooDockError::InvalidInput(format!(
+            "Tags cannot contain spaces: {}",
+            tag
+        )));
+    }
+    Ok(tag.to_string())
+}
+
+/// Tags compare case-insensitively, so `Urgent` and `urgent` are one tag.
+pub fn same_tag(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+pub fn has_tag(file: &FileItem, tag: &str) -> bool {
+    file.tags.iter().any(|t| same_tag(t, tag))
+}
+
+/// A search query split into `tag:` filters and the remaining words.
+#[derive(Debug, Default)]
+pub struct TagQuery {
+    pub tags: Vec<String>,
+    pub text: String,
+}
+
+impl TagQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = TagQuery::default();
+        let mut words = Vec::new();
+        for word in query.split_whitespace() {
+            match word.get(..TAG_PREFIX.len()) {
+                Some(prefix) if prefix.eq_ignore_ascii_case(TAG_PREFIX) => {
+                    let tag = &word[TAG_PREFIX.len()..];
+                    if !tag.is_empty() {
+                        parsed.tags.push(tag.to_string());
+                    }
+                }
+                _ => words.push(word),
+            }
+        }
+        parsed.text = words.join(" ");
+        parsed
+    }
+
+    /// Whether the item has every tag and the text in its name or note.
+    pub fn matches(&self, file: &FileItem) -> bool {
+        let text = self.text.to_lowercase();
+        self.tags.iter().all(|tag| has_tag(file, tag))
+            && (file.name.to_lowercase().contains(&text)
+                || file
+                    .note
+                    .as_ref()
+                    .is_some_and(|note| note.to_lowercase().contains(&text)))
+    }
+}
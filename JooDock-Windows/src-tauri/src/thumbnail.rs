@@ -0,0 +1,283 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::UriSchemeResponder;
+
+use crate::error::JooDockError;
+use crate::storage;
+
+/// URI scheme the webview loads thumbnails from, e.g.
+/// `convertFileSrc(path, "joodock-thumb") + "?size=64"`.
+pub const SCHEME: &str = "joodock-thumb";
+
+const THUMBNAILS_DIR: &str = "thumbnails";
+const DEFAULT_SIZE: u32 = 64;
+const MIN_SIZE: u32 = 16;
+const MAX_SIZE: u32 = 256;
+/// Thumbnails kept on disk; the oldest renderings go first.
+const CACHE_LIMIT: usize = 2000;
+/// Threads rendering thumbnails, so a long list cannot start one per file.
+const WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Source of the icon or preview the operating system shows for a file.
+pub trait IconProvider: Send + Sync {
+    /// PNG of at most `size`x`size` pixels, or `None` if there is nothing to show.
+    fn icon(&self, path: &Path, size: u32) -> Option<Vec<u8>>;
+}
+
+/// Used where the OS offers no icons; the UI keeps its generic icon.
+pub struct NoIcons;
+
+impl IconProvider for NoIcons {
+    fn icon(&self, _path: &Path, _size: u32) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Explorer's own thumbnail (or icon) for a file, via `IShellItemImageFactory`.
+#[cfg(windows)]
+pub struct ShellIcons;
+
+#[cfg(windows)]
+impl IconProvider for ShellIcons {
+    fn icon(&self, path: &Path, size: u32) -> Option<Vec<u8>> {
+        use windows::core::HSTRING;
+        use windows::Win32::Foundation::{HWND, SIZE};
+        use windows::Win32::Graphics::Gdi::{
+            DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO,
+            BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HGDIOBJ,
+        };
+        use windows::Win32::System::Com::{CoInitializeEx, COINIT_APARTMENTTHREADED};
+        use windows::Win32::UI::Shell::{
+            IShellItemImageFactory, SHCreateItemFromParsingName, SIIGBF_BIGGERSIZEOK,
+        };
+
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            let factory: IShellItemImageFactory =
+                SHCreateItemFromParsingName(&HSTRING::from(path.as_os_str()), None).ok()?;
+            let bitmap = factory
+                .GetImage(
+                    SIZE {
+                        cx: size as i32,
+                        cy: size as i32,
+                    },
+                    SIIGBF_BIGGERSIZEOK,
+                )
+                .ok()?;
+
+            let mut info = BITMAP::default();
+            GetObjectW(
+                HGDIOBJ(bitmap.0),
+                std::mem::size_of::<BITMAP>() as i32,
+                Some(&mut info as *mut BITMAP as *mut _),
+            );
+            let (width, height) = (info.bmWidth, info.bmHeight);
+
+            let mut header = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width,
+                    // Negative height asks for top-down rows
+                    biHeight: -height,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut pixels = vec![0u8; (width.max(0) * height.max(0) * 4) as usize];
+            let dc = GetDC(HWND::default());
+            let rows = GetDIBits(
+                dc,
+                bitmap,
+                0,
+                height as u32,
+                Some(pixels.as_mut_ptr() as *mut _),
+                &mut header,
+                DIB_RGB_COLORS,
+            );
+            ReleaseDC(HWND::default(), dc);
+            let _ = DeleteObject(HGDIOBJ(bitmap.0));
+            if rows == 0 {
+                return None;
+            }
+
+            // BGRA -> RGBA
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            let image = image::RgbaImage::from_raw(width as u32, height as u32, pixels)?;
+            encode_png(&image::DynamicImage::ImageRgba8(image))
+        }
+    }
+}
+
+fn provider() -> &'static dyn IconProvider {
+    #[cfg(windows)]
+    {
+        &ShellIcons
+    }
+    #[cfg(not(windows))]
+    {
+        &NoIcons
+    }
+}
+
+/// PNG thumbnail for `path`: a scaled-down copy for images, the shell icon
+/// otherwise. Results are cached on disk until the file changes.
+pub fn thumbnail(path: &Path, size: u32) -> Result<Vec<u8>, JooDockError> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let stat = fs::metadata(path).map_err(|e| JooDockError::io(path, e))?;
+
+    let dir = storage::get_data_dir()?.join(THUMBNAILS_DIR);
+    fs::create_dir_all(&dir).map_err(|e| JooDockError::io(&dir, e))?;
+    let key = cache_key(path, size);
+    let cached = dir.join(format!("{}-{}.png", key, version(&stat)));
+    if let Ok(png) = fs::read(&cached) {
+        return Ok(png);
+    }
+
+    let png = image_thumbnail(path, size)
+        .or_else(|| provider().icon(path, size))
+        .ok_or_else(|| {
+            JooDockError::InvalidInput(format!("No thumbnail for {}", path.display()))
+        })?;
+    // A failed cache write only costs a re-render next time
+    if fs::write(&cached, &png).is_ok() {
+        prune_cache(&dir, &key, &cached);
+    }
+
+    Ok(png)
+}
+
+/// Serves `joodock-thumb://localhost/<encoded path>?size=<px>`.
+pub fn respond(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let path = percent_encoding::percent_decode_str(request.uri().path().trim_start_matches('/'))
+        .decode_utf8_lossy()
+        .to_string();
+    let size = request
+        .uri()
+        .query()
+        .and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("size="))
+                .and_then(|size| size.parse().ok())
+        })
+        .unwrap_or(DEFAULT_SIZE);
+
+    let builder = Response::builder().header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    let response = match thumbnail(Path::new(&path), size) {
+        Ok(png) => builder
+            .header(header::CONTENT_TYPE, "image/png")
+            .header(header::CACHE_CONTROL, "max-age=3600")
+            .body(png),
+        Err(e) => builder
+            .status(StatusCode::NOT_FOUND)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&e).unwrap_or_default()),
+    };
+    response.unwrap_or_else(|_| Response::new(Vec::new()))
+}
+
+/// Answers `request` from one of the rendering threads.
+pub fn respond_later(request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    let job: Job = Box::new(move || responder.respond(respond(&request)));
+    // The workers only stop when the process does
+    let _ = workers().send(job);
+}
+
+fn workers() -> &'static mpsc::Sender<Job> {
+    static QUEUE: OnceLock<mpsc::Sender<Job>> = OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..WORKERS {
+            let receiver = Arc::clone(&receiver);
+            std::thread::spawn(move || loop {
+                let job = match receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => return,
+                };
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => return,
+                }
+            });
+        }
+        sender
+    })
+}
+
+pub fn encode_png(image: &image::DynamicImage) -> Option<Vec<u8>> {
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png).ok()?;
+    Some(png.into_inner())
+}
+
+fn image_thumbnail(path: &Path, size: u32) -> Option<Vec<u8>> {
+    // Only decode formats the image crate was built with
+    image::ImageFormat::from_path(path).ok()?;
+    let image = image::open(path).ok()?;
+    if image.width() <= size && image.height() <= size {
+        // `thumbnail` would scale small images up
+        return encode_png(&image);
+    }
+    encode_png(&image.thumbnail(size, size))
+}
+
+/// Names the renderings of `path` at `size`; stable across builds so the
+/// cache survives updates.
+fn cache_key(path: &Path, size: u32) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    hasher.update(size.to_le_bytes());
+    hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Changes whenever the file's length or mtime does.
+fn version(stat: &fs::Metadata) -> String {
+    let mtime = stat
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    format!("{:x}-{:x}", stat.len(), mtime)
+}
+
+/// Drops the older renderings of `key` (and files named by earlier versions of
+/// the cache), then the oldest thumbnails once there are more than `CACHE_LIMIT`.
+fn prune_cache(dir: &Path, key: &str, current: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let prefix = format!("{}-", key);
+    let mut kept: Vec<(SystemTime, PathBuf)> = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path != current && (name.starts_with(&prefix) || !name.contains('-')) {
+            let _ = fs::remove_file(&path);
+        } else if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+            kept.push((modified, path));
+        }
+    }
+
+    if kept.len() > CACHE_LIMIT {
+        kept.sort();
+        for (_, path) in &kept[..kept.len() - CACHE_LIMIT] {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
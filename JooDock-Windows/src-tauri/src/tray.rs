@@ -1,17 +1,23 @@
 use std::sync::atomic::Ordering;
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    App, Manager,
+    App, AppHandle, Manager, Runtime,
 };
 
+use crate::commands;
+use crate::error::JooDockError;
+use crate::smart;
+use crate::storage;
 use crate::POPUP_VISIBLE;
 
+const TRAY_ID: &str = "main";
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+
 pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
-    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&quit_item])?;
+    let menu = build_menu(app)?;
 
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .menu_on_left_click(false)
@@ -19,7 +25,11 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
             "quit" => {
                 app.exit(0);
             }
-            _ => {}
+            id => {
+                if let Some(profile_id) = id.strip_prefix(PROFILE_ITEM_PREFIX) {
+                    let _ = commands::profiles::switch_profile(app.clone(), profile_id.to_string());
+                }
+            }
         })
         .on_tray_icon_event(|tray, event| {
             if let TrayIconEvent::Click {
@@ -38,6 +48,7 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
                         let _ = window.show();
                         let _ = window.set_focus();
                         POPUP_VISIBLE.store(true, Ordering::SeqCst);
+                        smart::refresh_in_background(app);
                     }
                 }
             }
@@ -45,4 +56,45 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         .build(app)?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Rebuilds the tray menu after profiles are added, removed or switched.
+pub fn refresh_menu(app: &AppHandle) -> Result<(), JooDockError> {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_menu(Some(build_menu(app)?))?;
+    }
+    Ok(())
+}
+
+fn build_menu<R: Runtime, M: Manager<R>>(manager: &M) -> Result<Menu<R>, JooDockError> {
+    // A broken profiles.json should not take the tray down with it
+    let profiles = storage::load_profiles().unwrap_or_default();
+
+    let profile_items = profiles
+        .profiles
+        .iter()
+        .map(|profile| {
+            CheckMenuItem::with_id(
+                manager,
+                format!("{}{}", PROFILE_ITEM_PREFIX, profile.id),
+                &profile.name,
+                true,
+                profile.id == profiles.active_id,
+                None::<&str>,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let profile_refs: Vec<&dyn IsMenuItem<R>> = profile_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<R>)
+        .collect();
+
+    let profiles_menu = Submenu::with_items(manager, "Profiles", true, &profile_refs)?;
+    let separator = PredefinedMenuItem::separator(manager)?;
+    let quit_item = MenuItem::with_id(manager, "quit", "Quit", true, None::<&str>)?;
+
+    Ok(Menu::with_items(
+        manager,
+        &[&profiles_menu, &separator, &quit_item],
+    )?)
+}
@@ -2,6 +2,7 @@ import { useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useAppStore } from "./stores/appStore";
+import { FileItem } from "./types";
 import { Header } from "./components/Header";
 import { FileList } from "./components/FileList";
 import { Footer } from "./components/Footer";
@@ -9,40 +10,79 @@ import { AddGroupModal } from "./components/AddGroupModal";
 
 function App() {
   const loadData = useAppStore((state) => state.loadData);
-  const addFile = useAppStore((state) => state.addFile);
+  const addFiles = useAppStore((state) => state.addFiles);
+  const addUrl = useAppStore((state) => state.addUrl);
+  const setSmartGroupFiles = useAppStore((state) => state.setSmartGroupFiles);
+  const undo = useAppStore((state) => state.undo);
+  const redo = useAppStore((state) => state.redo);
   const isAddGroupModalOpen = useAppStore((state) => state.isAddGroupModalOpen);
 
   useEffect(() => {
     loadData();
 
-    // Handle ESC key
+    // Handle ESC key, and Ctrl+Z / Ctrl+Y for undo and redo outside text fields
     const handleKeyDown = (e: KeyboardEvent) => {
       if (e.key === "Escape") {
         invoke("hide_popup");
+        return;
+      }
+      const inTextField = e.target instanceof HTMLInputElement;
+      if (!e.ctrlKey || inTextField) return;
+      const key = e.key.toLowerCase();
+      if (key === "z" && !e.shiftKey) {
+        e.preventDefault();
+        undo();
+      } else if (key === "y" || (key === "z" && e.shiftKey)) {
+        e.preventDefault();
+        redo();
       }
     };
 
     window.addEventListener("keydown", handleKeyDown);
 
+    // Reload everything when the active profile changes (e.g. from the tray)
+    const unlistenProfile = listen("profile-changed", () => {
+      loadData();
+    });
+    const unlistenSync = listen("sync-completed", () => {
+      loadData();
+    });
+    const unlistenJanitor = listen("janitor-swept", () => {
+      loadData();
+    });
+    const unlistenSmart = listen<Record<string, FileItem[]>>(
+      "smart-groups-updated",
+      (event) => setSmartGroupFiles(event.payload)
+    );
+
     return () => {
       window.removeEventListener("keydown", handleKeyDown);
+      unlistenProfile.then((unlisten) => unlisten());
+      unlistenSync.then((unlisten) => unlisten());
+      unlistenJanitor.then((unlisten) => unlisten());
+      unlistenSmart.then((unlisten) => unlisten());
     };
-  }, [loadData]);
+  }, [loadData, setSmartGroupFiles, undo, redo]);
 
   // Handle file drop
   const handleDrop = useCallback(
     (e: React.DragEvent) => {
       e.preventDefault();
-      const files = Array.from(e.dataTransfer.files);
-      files.forEach((file) => {
+      const paths = Array.from(e.dataTransfer.files)
         // @ts-ignore - path exists on File in Tauri
-        const path = file.path || file.name;
-        if (path) {
-          addFile(path);
-        }
-      });
+        .map((file) => (file.path || file.name) as string)
+        .filter(Boolean);
+      if (paths.length > 0) {
+        addFiles(paths);
+        return;
+      }
+      // Links dragged from a browser
+      const url = e.dataTransfer.getData("text/uri-list").split("\n")[0]?.trim();
+      if (url) {
+        addUrl(url);
+      }
     },
-    [addFile]
+    [addFiles, addUrl]
   );
 
   const handleDragOver = useCallback((e: React.DragEvent) => {
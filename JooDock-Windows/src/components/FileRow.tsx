@@ -8,11 +8,14 @@ import {
   Archive,
   Code,
   Folder,
+  Globe,
+  SquareTerminal,
   Table,
   Presentation,
   X,
 } from "lucide-react";
-import { FileItem, getFileType, FileType } from "../types";
+import { useAppStore } from "../stores/appStore";
+import { FileItem, getFileType, FileType, thumbnailUrl } from "../types";
 
 interface FileRowProps {
   file: FileItem;
@@ -43,9 +46,18 @@ export function FileRow({
   showRemove = true,
 }: FileRowProps) {
   const [isHovered, setIsHovered] = useState(false);
+  const [thumbnailFailed, setThumbnailFailed] = useState(false);
+  const setSearchQuery = useAppStore((state) => state.setSearchQuery);
 
-  const fileType = getFileType(file.name, file.path.endsWith("/") || file.path.endsWith("\\"));
-  const Icon = iconMap[fileType];
+  // `kind` reports links as "symlink"; `itemType` follows them to the folder
+  const fileType = getFileType(file.name, file.kind === "directory" || file.itemType === "folder");
+  const Icon =
+    file.itemType === "url"
+      ? Globe
+      : file.itemType === "command"
+        ? SquareTerminal
+        : iconMap[fileType];
+  const onDisk = file.itemType !== "url" && file.itemType !== "command";
 
   // Truncate path in the middle
   const truncatePath = (path: string, maxLen: number = 40) => {
@@ -59,22 +71,54 @@ export function FileRow({
     <div
       className="group flex items-center gap-3 px-2 py-1.5 rounded-lg hover:bg-gray-100/80 dark:hover:bg-gray-800/80 cursor-pointer transition-colors"
       onClick={onOpen}
+      title={file.note ?? undefined}
       onMouseEnter={() => setIsHovered(true)}
       onMouseLeave={() => setIsHovered(false)}
     >
       {/* Icon */}
-      <div className="flex-shrink-0 w-8 h-8 flex items-center justify-center bg-gray-100 dark:bg-gray-800 rounded-lg">
-        <Icon className="w-4 h-4 text-gray-500 dark:text-gray-400" />
+      <div
+        className="flex-shrink-0 w-8 h-8 flex items-center justify-center bg-gray-100 dark:bg-gray-800 rounded-lg"
+        style={file.color ? { boxShadow: `inset 3px 0 0 ${file.color}` } : undefined}
+      >
+        {onDisk && file.kind !== "missing" && !thumbnailFailed ? (
+          <img
+            src={thumbnailUrl(file.path)}
+            alt=""
+            className="w-6 h-6 object-contain"
+            onError={() => setThumbnailFailed(true)}
+          />
+        ) : (
+          <Icon className="w-4 h-4 text-gray-500 dark:text-gray-400" />
+        )}
       </div>
 
       {/* File info */}
       <div className="flex-1 min-w-0">
         <div className="text-xs font-medium text-gray-800 dark:text-gray-200 truncate">
-          {file.name}
+          {file.displayName ?? file.alias ?? file.name}
+          {file.parentHint && (
+            <span className="ml-1 font-normal text-gray-400">{file.parentHint}</span>
+          )}
         </div>
         <div className="text-[10px] text-gray-400 truncate">
           {truncatePath(file.path)}
         </div>
+        {file.tags?.length > 0 && (
+          <div className="flex gap-1 mt-0.5 overflow-hidden">
+            {file.tags.map((tag) => (
+              <button
+                key={tag}
+                onClick={(e) => {
+                  e.stopPropagation();
+                  setSearchQuery(`tag:${tag}`);
+                }}
+                className="px-1 text-[9px] leading-4 rounded bg-blue-100 dark:bg-blue-900/40 text-blue-600 dark:text-blue-300 hover:bg-blue-200 dark:hover:bg-blue-900"
+              >
+                #{tag}
+              </button>
+            ))}
+          </div>
+        )}
       </div>
 
       {/* Remove button */}
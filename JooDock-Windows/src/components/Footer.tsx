@@ -3,7 +3,7 @@ import { useAppStore } from "../stores/appStore";
 import { open } from "@tauri-apps/plugin-shell";
 
 export function Footer() {
-  const addFile = useAppStore((state) => state.addFile);
+  const addFiles = useAppStore((state) => state.addFiles);
   const setAddGroupModalOpen = useAppStore(
     (state) => state.setAddGroupModalOpen
   );
@@ -18,11 +18,7 @@ export function Footer() {
       });
       if (files) {
         const paths = Array.isArray(files) ? files : [files];
-        for (const path of paths) {
-          if (typeof path === "string") {
-            addFile(path);
-          }
-        }
+        addFiles(paths.filter((path): path is string => typeof path === "string"));
       }
     } catch (error) {
       console.error("Failed to open file picker:", error);
@@ -5,6 +5,7 @@ import {
   MoreHorizontal,
   Plus,
   Pencil,
+  Play,
   Trash2,
   Briefcase,
   User,
@@ -29,7 +30,7 @@ interface GroupSectionProps {
 
 const iconComponents: Record<
   string,
-  React.ComponentType<{ className?: string }>
+  React.ComponentType<{ className?: string; style?: React.CSSProperties }>
 > = {
   briefcase: Briefcase,
   user: User,
@@ -56,7 +57,8 @@ export function GroupSection({ group, isUngrouped = false }: GroupSectionProps)
   const removeGroup = useAppStore((state) => state.removeGroup);
   const removeFile = useAppStore((state) => state.removeFile);
   const openFile = useAppStore((state) => state.openFile);
-  const addFile = useAppStore((state) => state.addFile);
+  const openGroup = useAppStore((state) => state.openGroup);
+  const addFiles = useAppStore((state) => state.addFiles);
 
   const files = getFilesInGroup(isUngrouped ? null : group.id);
   const Icon = iconComponents[group.icon] || Folder;
@@ -84,16 +86,15 @@ export function GroupSection({ group, isUngrouped = false }: GroupSectionProps)
     (e: React.DragEvent) => {
       e.preventDefault();
       setIsDragOver(false);
-      const droppedFiles = Array.from(e.dataTransfer.files);
-      droppedFiles.forEach((file) => {
+      const paths = Array.from(e.dataTransfer.files)
         // @ts-ignore
-        const path = file.path || file.name;
-        if (path) {
-          addFile(path, isUngrouped ? null : group.id);
-        }
-      });
+        .map((file) => (file.path || file.name) as string)
+        .filter(Boolean);
+      if (paths.length > 0) {
+        addFiles(paths, isUngrouped ? null : group.id);
+      }
     },
-    [addFile, group.id, isUngrouped]
+    [addFiles, group.id, isUngrouped]
   );
 
   return (
@@ -123,7 +124,10 @@ export function GroupSection({ group, isUngrouped = false }: GroupSectionProps)
           </button>
         )}
 
-        <Icon className="w-3 h-3 text-blue-500" />
+        <Icon
+          className="w-3 h-3 text-blue-500"
+          style={group.color ? { color: group.color } : undefined}
+        />
 
         {isEditing ? (
           <input
@@ -161,6 +165,16 @@ export function GroupSection({ group, isUngrouped = false }: GroupSectionProps)
                   onClick={() => setShowMenu(false)}
                 />
                 <div className="absolute right-0 top-full mt-1 w-32 bg-white dark:bg-gray-800 rounded-lg shadow-lg border border-gray-200 dark:border-gray-700 py-1 z-20">
+                  <button
+                    onClick={() => {
+                      openGroup(group.id);
+                      setShowMenu(false);
+                    }}
+                    className="w-full flex items-center gap-2 px-3 py-1.5 text-xs hover:bg-gray-100 dark:hover:bg-gray-700"
+                  >
+                    <Play className="w-3 h-3" />
+                    Open all
+                  </button>
                   <button
                     onClick={() => {
                       setEditName(group.name);
@@ -207,7 +221,7 @@ export function GroupSection({ group, isUngrouped = false }: GroupSectionProps)
               <FileRow
                 key={file.id}
                 file={file}
-                onOpen={() => openFile(file.path)}
+                onOpen={() => openFile(file.path, file.id)}
                 onRemove={() => removeFile(file.id)}
               />
             ))
@@ -1,6 +1,16 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
-import { FileItem, FileGroup, Settings, UNGROUPED_ID } from "../types";
+import {
+  AddResult,
+  FileItem,
+  FileGroup,
+  GroupLaunch,
+  GroupNode,
+  JooDockError,
+  Settings,
+  UNGROUPED_ID,
+  flattenGroups,
+} from "../types";
 
 interface AppState {
   // Data
@@ -8,6 +18,8 @@ interface AppState {
   groups: FileGroup[];
   recentFiles: FileItem[];
   searchResults: FileItem[];
+  smartGroupFiles: Record<string, FileItem[]>;
+  archivedFiles: FileItem[];
   settings: Settings;
 
   // UI State
@@ -19,13 +31,37 @@ interface AppState {
   loadData: () => Promise<void>;
   setSearchQuery: (query: string) => void;
   addFile: (path: string, groupId?: string | null) => Promise<void>;
+  addFiles: (paths: string[], groupId?: string | null) => Promise<AddResult[]>;
+  addUrl: (url: string, groupId?: string | null) => Promise<void>;
+  addCommand: (
+    program: string,
+    args: string[],
+    workingDir?: string | null,
+    groupId?: string | null
+  ) => Promise<void>;
   removeFile: (id: string) => Promise<void>;
-  openFile: (path: string) => Promise<void>;
-  addGroup: (name: string, icon: string) => Promise<void>;
+  loadArchive: () => Promise<void>;
+  restoreFile: (id: string) => Promise<void>;
+  purgeArchive: (ids?: string[]) => Promise<void>;
+  tagFile: (id: string, tags: string[]) => Promise<void>;
+  untagFile: (id: string, tags: string[]) => Promise<void>;
+  setNote: (id: string, note: string | null) => Promise<void>;
+  setAlias: (id: string, alias: string | null) => Promise<void>;
+  setExpiry: (id: string, expiresAt: string | null) => Promise<void>;
+  setFileColor: (id: string, color: string | null) => Promise<void>;
+  setGroupColor: (id: string, color: string | null) => Promise<void>;
+  moveFile: (id: string, groupId: string | null, index: number) => Promise<void>;
+  openFile: (path: string, id?: string) => Promise<void>;
+  openGroup: (id: string, delayMs?: number) => Promise<GroupLaunch | null>;
+  addGroup: (name: string, icon: string, parentId?: string | null) => Promise<void>;
   removeGroup: (id: string) => Promise<void>;
   renameGroup: (id: string, newName: string) => Promise<void>;
   toggleGroup: (id: string) => Promise<void>;
+  reorderGroups: (ids: string[]) => Promise<void>;
   setAddGroupModalOpen: (open: boolean) => void;
+  setSmartGroupFiles: (files: Record<string, FileItem[]>) => void;
+  undo: () => Promise<void>;
+  redo: () => Promise<void>;
   performSearch: (query: string) => Promise<void>;
 
   // Computed
@@ -39,10 +75,16 @@ export const useAppStore = create<AppState>((set, get) => ({
   groups: [],
   recentFiles: [],
   searchResults: [],
+  smartGroupFiles: {},
+  archivedFiles: [],
   settings: {
     hoverZoneWidth: 300,
     hoverZoneHeight: 50,
     hoverDelay: 0.3,
+    syncFolder: null,
+    confirmCommands: true,
+    inbox: { groupId: null, maxAgeDays: 7, action: "archive" },
+    archiveRetentionDays: 30,
   },
   searchQuery: "",
   isSearching: false,
@@ -51,13 +93,13 @@ export const useAppStore = create<AppState>((set, get) => ({
   // Load initial data
   loadData: async () => {
     try {
-      const [files, groups, recentFiles, settings] = await Promise.all([
+      const [files, groupTree, recentFiles, settings] = await Promise.all([
         invoke<FileItem[]>("get_files"),
-        invoke<FileGroup[]>("get_groups"),
+        invoke<GroupNode[]>("get_groups"),
         invoke<FileItem[]>("get_recent_files"),
         invoke<Settings>("get_settings"),
       ]);
-      set({ files, groups, recentFiles, settings });
+      set({ files, groups: flattenGroups(groupTree), recentFiles, settings });
     } catch (error) {
       console.error("Failed to load data:", error);
     }
@@ -94,6 +136,46 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
+  addFiles: async (paths: string[], groupId?: string | null) => {
+    try {
+      const results = await invoke<AddResult[]>("add_files", { paths, groupId });
+      const added = results.flatMap((r) => (r.status === "added" ? [r.file] : []));
+      set((state) => ({ files: [...state.files, ...added] }));
+      return results;
+    } catch (error) {
+      console.error("Failed to add files:", error);
+      return [];
+    }
+  },
+
+  addUrl: async (url: string, groupId?: string | null) => {
+    try {
+      const file = await invoke<FileItem>("add_url", { url, groupId });
+      set((state) => ({ files: [...state.files, file] }));
+    } catch (error) {
+      console.error("Failed to add link:", error);
+    }
+  },
+
+  addCommand: async (
+    program: string,
+    args: string[],
+    workingDir?: string | null,
+    groupId?: string | null
+  ) => {
+    try {
+      const file = await invoke<FileItem>("add_command", {
+        program,
+        args,
+        workingDir,
+        groupId,
+      });
+      set((state) => ({ files: [...state.files, file] }));
+    } catch (error) {
+      console.error("Failed to add command:", error);
+    }
+  },
+
   removeFile: async (id: string) => {
     try {
       await invoke("remove_file", { id });
@@ -103,18 +185,167 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
-  openFile: async (path: string) => {
+  loadArchive: async () => {
+    try {
+      const archivedFiles = await invoke<FileItem[]>("get_archive");
+      set({ archivedFiles });
+    } catch (error) {
+      console.error("Failed to load archive:", error);
+    }
+  },
+
+  restoreFile: async (id: string) => {
+    try {
+      await invoke("restore_file", { id });
+      const files = await invoke<FileItem[]>("get_files");
+      set((state) => ({
+        files,
+        archivedFiles: state.archivedFiles.filter((f) => f.id !== id),
+      }));
+    } catch (error) {
+      console.error("Failed to restore file:", error);
+    }
+  },
+
+  purgeArchive: async (ids?: string[]) => {
+    try {
+      const purged = await invoke<FileItem[]>("purge_archive", { ids: ids ?? null });
+      const purgedIds = new Set(purged.map((f) => f.id));
+      set((state) => ({
+        archivedFiles: state.archivedFiles.filter((f) => !purgedIds.has(f.id)),
+      }));
+    } catch (error) {
+      console.error("Failed to empty archive:", error);
+    }
+  },
+
+  tagFile: async (id: string, tags: string[]) => {
+    try {
+      const file = await invoke<FileItem>("tag_file", { id, tags });
+      set((state) => ({ files: state.files.map((f) => (f.id === id ? file : f)) }));
+    } catch (error) {
+      console.error("Failed to tag file:", error);
+    }
+  },
+
+  untagFile: async (id: string, tags: string[]) => {
     try {
-      await invoke("open_file", { path });
+      const file = await invoke<FileItem>("untag_file", { id, tags });
+      set((state) => ({ files: state.files.map((f) => (f.id === id ? file : f)) }));
     } catch (error) {
+      console.error("Failed to untag file:", error);
+    }
+  },
+
+  setNote: async (id: string, note: string | null) => {
+    try {
+      const file = await invoke<FileItem>("set_note", { id, note });
+      set((state) => ({ files: state.files.map((f) => (f.id === id ? file : f)) }));
+    } catch (error) {
+      console.error("Failed to set note:", error);
+    }
+  },
+
+  setAlias: async (id: string, alias: string | null) => {
+    try {
+      await invoke("set_alias", { id, alias });
+      // Other items' parent hints may change with the new name
+      const files = await invoke<FileItem[]>("get_files");
+      set({ files });
+    } catch (error) {
+      console.error("Failed to rename item:", error);
+    }
+  },
+
+  setExpiry: async (id: string, expiresAt: string | null) => {
+    try {
+      const file = await invoke<FileItem>("set_expiry", { id, expiresAt });
+      set((state) => ({
+        files: state.files.map((f) => (f.id === id ? { ...f, expiresAt: file.expiresAt } : f)),
+      }));
+    } catch (error) {
+      console.error("Failed to set expiry:", error);
+    }
+  },
+
+  setFileColor: async (id: string, color: string | null) => {
+    try {
+      await invoke("set_file_color", { id, color });
+      set((state) => ({
+        files: state.files.map((f) => (f.id === id ? { ...f, color } : f)),
+      }));
+    } catch (error) {
+      console.error("Failed to set item color:", error);
+    }
+  },
+
+  setGroupColor: async (id: string, color: string | null) => {
+    try {
+      const group = await invoke<FileGroup>("set_group_color", { id, color });
+      set((state) => ({
+        groups: state.groups.map((g) => (g.id === id ? { ...g, color: group.color } : g)),
+      }));
+    } catch (error) {
+      console.error("Failed to set group color:", error);
+    }
+  },
+
+  moveFile: async (id: string, groupId: string | null, index: number) => {
+    try {
+      await invoke("move_file", { id, groupId, index });
+      // Sort orders of both groups are renumbered on the backend
+      const files = await invoke<FileItem[]>("get_files");
+      set({ files });
+    } catch (error) {
+      console.error("Failed to move file:", error);
+    }
+  },
+
+  openFile: async (path: string, id?: string) => {
+    try {
+      await invoke("open_file", { path, id });
+    } catch (error) {
+      // Pinned commands ask before they run
+      const { code, message } = error as JooDockError;
+      if (code === "confirmation_required" && window.confirm(message)) {
+        await invoke("open_file", { path, id, confirmed: true }).catch((error) =>
+          console.error("Failed to open file:", error)
+        );
+        return;
+      }
       console.error("Failed to open file:", error);
     }
   },
 
+  openGroup: async (id: string, delayMs?: number) => {
+    const open = (confirmed?: boolean) =>
+      invoke<GroupLaunch>("open_group", { id, delayMs, confirmed });
+    try {
+      let launch: GroupLaunch;
+      try {
+        launch = await open();
+      } catch (error) {
+        const { code, message } = error as JooDockError;
+        if (code !== "confirmation_required") throw error;
+        if (!window.confirm(message)) return null;
+        launch = await open(true);
+      }
+      for (const failure of launch.failed) {
+        console.error(`Failed to open ${failure.name}:`, failure.error);
+      }
+      const files = await invoke<FileItem[]>("get_files");
+      set({ files });
+      return launch;
+    } catch (error) {
+      console.error("Failed to open group:", error);
+      return null;
+    }
+  },
+
   // Group Actions
-  addGroup: async (name: string, icon: string) => {
+  addGroup: async (name: string, icon: string, parentId?: string | null) => {
     try {
-      const group = await invoke<FileGroup>("add_group", { name, icon });
+      const group = await invoke<FileGroup>("add_group", { name, icon, parentId });
       set((state) => ({ groups: [...state.groups, group] }));
     } catch (error) {
       console.error("Failed to add group:", error);
@@ -124,12 +355,12 @@ export const useAppStore = create<AppState>((set, get) => ({
   removeGroup: async (id: string) => {
     try {
       await invoke("remove_group", { id });
-      set((state) => ({
-        groups: state.groups.filter((g) => g.id !== id),
-        files: state.files.map((f) =>
-          f.groupId === id ? { ...f, groupId: null } : f
-        ),
-      }));
+      // Files and sub-groups move to the parent group; reload both
+      const [files, groupTree] = await Promise.all([
+        invoke<FileItem[]>("get_files"),
+        invoke<GroupNode[]>("get_groups"),
+      ]);
+      set({ files, groups: flattenGroups(groupTree) });
     } catch (error) {
       console.error("Failed to remove group:", error);
     }
@@ -161,14 +392,49 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
+  reorderGroups: async (ids: string[]) => {
+    try {
+      const groupTree = await invoke<GroupNode[]>("reorder_groups", { ids });
+      set({ groups: flattenGroups(groupTree) });
+    } catch (error) {
+      console.error("Failed to reorder groups:", error);
+    }
+  },
+
   setAddGroupModalOpen: (open: boolean) => {
     set({ isAddGroupModalOpen: open });
   },
 
+  setSmartGroupFiles: (smartGroupFiles: Record<string, FileItem[]>) => {
+    set({ smartGroupFiles });
+  },
+
+  // History
+  undo: async () => {
+    try {
+      if (await invoke<string | null>("undo")) {
+        await get().loadData();
+      }
+    } catch (error) {
+      console.error("Failed to undo:", error);
+    }
+  },
+
+  redo: async () => {
+    try {
+      if (await invoke<string | null>("redo")) {
+        await get().loadData();
+      }
+    } catch (error) {
+      console.error("Failed to redo:", error);
+    }
+  },
+
   // Computed getters
   getFilesInGroup: (groupId: string | null) => {
-    const { files, searchQuery } = get();
-    let filtered = files;
+    const { files, groups, smartGroupFiles, searchQuery } = get();
+    const isSmart = groups.some((g) => g.id === groupId && g.smart);
+    let filtered = isSmart ? smartGroupFiles[groupId as string] ?? [] : files;
 
     if (searchQuery) {
       const q = searchQuery.toLowerCase();
@@ -178,15 +444,18 @@ export const useAppStore = create<AppState>((set, get) => ({
       );
     }
 
+    const bySortOrder = (a: FileItem, b: FileItem) => a.sortOrder - b.sortOrder;
+
     if (groupId === null || groupId === UNGROUPED_ID) {
-      return filtered.filter((f) => !f.groupId);
+      return filtered.filter((f) => !f.groupId).sort(bySortOrder);
     }
-    return filtered.filter((f) => f.groupId === groupId);
+    return filtered.filter((f) => f.groupId === groupId).sort(bySortOrder);
   },
 
   getGroupsWithUngrouped: () => {
     const { groups, files } = get();
-    const sorted = [...groups].sort((a, b) => a.sortOrder - b.sortOrder);
+    // Already in tree order (parents before children, siblings by sortOrder)
+    const sorted = [...groups];
     const ungroupedFiles = files.filter((f) => !f.groupId);
 
     if (ungroupedFiles.length > 0) {
@@ -1,3 +1,5 @@
+import { convertFileSrc } from "@tauri-apps/api/core";
+
 export interface FileItem {
   id: string;
   name: string;
@@ -5,6 +7,105 @@ export interface FileItem {
   groupId: string | null;
   addedAt: string;
   lastAccessedAt: string | null;
+  sortOrder: number;
+  kind: FileKind;
+  fingerprint?: Fingerprint | null;
+  itemType: ItemType;
+  /** Only set for command items */
+  launch?: Launch | null;
+  tags: string[];
+  note?: string | null;
+  /** Shown instead of the file name */
+  alias?: string | null;
+  color?: string | null;
+  /** Temporary pins are cleared by the janitor after this time */
+  expiresAt?: string | null;
+  /** Set while the item sits in the archive */
+  deletedAt?: string | null;
+  /** When the item last changed groups */
+  movedAt?: string | null;
+  /** Attached by get_files, search_files and get_recent_files */
+  metadata?: FileMetadata | null;
+  displayName?: string;
+  /** Parent folders that tell apart items with the same display name */
+  parentHint?: string | null;
+}
+
+export interface FileMetadata {
+  size: number | null;
+  modifiedAt: string | null;
+  createdAt: string | null;
+  extension: string | null;
+  mime: string | null;
+  readOnly: boolean;
+  hidden: boolean;
+}
+
+export type ItemType = "file" | "folder" | "url" | "command";
+
+export interface Launch {
+  args: string[];
+  workingDir: string | null;
+}
+
+export type FileKind = "file" | "directory" | "symlink" | "missing";
+
+export interface DirectoryListing {
+  entries: FileItem[];
+  /** The folder has more children than `entries` holds */
+  truncated: boolean;
+}
+
+export interface Fingerprint {
+  size: number;
+  fileId: number | null;
+}
+
+export interface ArchiveEntry {
+  name: string;
+  size: number;
+  isDir: boolean;
+}
+
+export type Preview =
+  | { type: "text"; lines: string[]; language: string | null; truncated: boolean }
+  | { type: "image"; width: number; height: number; thumbnail: string | null }
+  | { type: "archive"; format: string; entries: ArchiveEntry[]; truncated: boolean }
+  | { type: "pdf"; pages: number | null; truncated: boolean }
+  | { type: "directory"; entries: string[]; truncated: boolean }
+  | { type: "none" };
+
+export type ConflictPolicy = "fail" | "keepBoth" | "replace";
+
+export type FileOperation =
+  | { op: "rename"; id: string; from: string; to: string }
+  | { op: "move"; id: string; from: string; to: string }
+  | { op: "duplicate"; id: string; path: string }
+  | { op: "trash"; file: FileItem };
+
+export type AddResult =
+  | { status: "added"; path: string; file: FileItem }
+  | { status: "duplicate"; path: string; existing: string }
+  | { status: "missing"; path: string }
+  | { status: "failed"; path: string; error: JooDockError };
+
+export interface TagCount {
+  tag: string;
+  count: number;
+}
+
+export interface GroupLaunch {
+  opened: string[];
+  skipped: string[];
+  failed: { id: string; name: string; error: JooDockError }[];
+}
+
+export type RelocateMethod = "fileId" | "nearOldLocation" | "search";
+
+export interface HealthIssue {
+  file: FileItem;
+  /** Best first; `sizeMatches` means the size is unchanged since pinning */
+  candidates: { path: string; method: RelocateMethod; sizeMatches: boolean }[];
 }
 
 export interface FileGroup {
@@ -12,14 +113,117 @@ export interface FileGroup {
   name: string;
   icon: string;
   sortOrder: number;
+  parentId?: string | null;
+  smart?: SmartRule | null;
   isExpanded: boolean;
   createdAt: string;
+  color?: string | null;
+}
+
+export type SmartRule =
+  | { type: "search"; query: string }
+  | { type: "folderGlob"; folder: string; pattern: string }
+  | { type: "modifiedWithin"; folder: string | null; days: number }
+  | { type: "mostOpened"; days: number; limit: number };
+
+export type RuleMatcher =
+  | { type: "extension"; extensions: string[] }
+  | { type: "pathPrefix"; prefix: string }
+  | { type: "glob"; pattern: string }
+  | { type: "nameRegex"; pattern: string };
+
+export interface RoutingRule {
+  id: string;
+  matcher: RuleMatcher;
+  groupId: string;
+  enabled: boolean;
+}
+
+export interface GroupNode extends FileGroup {
+  children: GroupNode[];
+}
+
+export function flattenGroups(nodes: GroupNode[]): FileGroup[] {
+  return nodes.flatMap(({ children, ...group }) => [
+    group,
+    ...flattenGroups(children),
+  ]);
 }
 
 export interface Settings {
   hoverZoneWidth: number;
   hoverZoneHeight: number;
   hoverDelay: number;
+  syncFolder?: string | null;
+  confirmCommands: boolean;
+  inbox: InboxSettings;
+  /** Days removed items stay in the archive; 0 keeps them */
+  archiveRetentionDays: number;
+}
+
+export type InboxAction = "archive" | "remove";
+
+export interface InboxSettings {
+  groupId: string | null;
+  maxAgeDays: number;
+  action: InboxAction;
+}
+
+export interface CleanupEntry {
+  at: string;
+  reason: "expired" | "inbox" | "retention";
+  action: InboxAction;
+  file: FileItem;
+}
+
+export interface SyncReport {
+  pushed: number;
+  pulled: number;
+  conflicts: { entity: "file" | "group"; id: string; winnerDevice: string }[];
+  skippedLogs: string[];
+  changed: boolean;
+}
+
+export interface Profile {
+  id: string;
+  name: string;
+  ownSettings: boolean;
+  createdAt: string;
+}
+
+export interface ProfileList {
+  activeId: string;
+  profiles: Profile[];
+}
+
+export type ImportMode = "replace" | "merge" | "mergeIntoNewGroup";
+
+export interface ImportPreview {
+  groupsAdded: string[];
+  groupsMerged: string[];
+  groupsRemoved: string[];
+  filesAdded: string[];
+  filesSkipped: string[];
+  filesRemoved: string[];
+  settingsChanged: boolean;
+}
+
+export interface JooDockError {
+  code:
+    | "file_missing"
+    | "not_found"
+    | "duplicate"
+    | "invalid_input"
+    | "confirmation_required"
+    | "permission_denied"
+    | "disk_full"
+    | "io"
+    | "corrupt_data"
+    | "serialization"
+    | "shell"
+    | "window";
+  message: string;
+  details: Record<string, unknown> | null;
 }
 
 export const UNGROUPED_ID = "00000000-0000-0000-0000-000000000000";
@@ -124,4 +328,9 @@ export function getFileIcon(fileType: FileType): string {
   };
 
   return iconMap[fileType];
-}
\ No newline at end of file
+}
+
+/** URL of the backend-rendered thumbnail (or shell icon) for a file. */
+export function thumbnailUrl(path: string, size: number = 64): string {
+  return `${convertFileSrc(path, "joodock-thumb")}?size=${size}`;
+}